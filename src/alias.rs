@@ -1,26 +1,53 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::env;
-use std::io::{Error, ErrorKind, BufReader};
-use std::io::prelude::*;
-use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::fs;
 use regex::{Regex,Captures};
+use serde::{Deserialize, Serialize};
+use crate::error::JumpcutError;
+use crate::param::Param;
+use crate::shell::Shell;
+use crate::paths::*;
 use crate::utils::*;
 
+/// Version of the structured alias file format that is written by `Alias::write`
+pub const ALIAS_FORMAT_VERSION: u32 = 1;
+
+/// Matches all parameter placeholders: ?[spec] (group 1), ?1 (group 2) and ?@ / ?* (group 3)
+const PLACEHOLDER_REGEX: &str = r"\?(?:\[([^\]]*)\]|([0-9]+)|([@*]))";
+
+/// Provides the value for a parameter that has no (valid) value: it is given the alias, the parameter, and the reason
+/// why the value that was provided is invalid (if any)
+pub type MissingArgHandler<'a> = dyn Fn(&Alias, &Param, Option<&str>) -> Result<String, JumpcutError> + 'a;
+
+#[derive(Clone)]
 pub struct Alias {
   alias: String,
   command: String,
   description: String,
   confirm: i8, // 0: no confirmation required ; 1: y/n confirmation ; 2: must confirm by entering alias name
+  extra_fields: BTreeMap<String, toml::Value>, // any fields we don't know about (yet), kept as-is
   storage_path: PathBuf
 }
 
+/// On-disk representation of an alias file (structured format)
+#[derive(Serialize, Deserialize)]
+struct AliasFile {
+  version: u32,
+  command: String,
+  #[serde(default)]
+  description: String,
+  #[serde(default)]
+  confirm: i8,
+  #[serde(flatten)]
+  extra_fields: BTreeMap<String, toml::Value>
+}
+
 impl Alias {
   /// Constructor
   /// 
   /// `alias`       : alias name
-  /// `cmd`         : the command that this alias expands to; arguments are represented as ?1, ?2, etc. ; the present working directory is represented as $pwd
+  /// `cmd`         : the command (or multi-line script) that this alias expands to; arguments are represented as ?[name] or ?1, ?2, etc. ; the present working directory is represented as $pwd
   /// `description` : an optional description of what this alias does
   /// `confirm`     : if true, a confirmation prompt is shown whenever executing this alias
   /// `is_shared`   : if true, this alias is in the shared storage folder (rather than the default folder)
@@ -30,6 +57,7 @@ impl Alias {
       command: cmd.to_string(),
       description: description.to_string(),
       confirm: confirm,
+      extra_fields: BTreeMap::new(),
       storage_path: storage_path
     };
   }
@@ -46,43 +74,76 @@ impl Alias {
   pub fn update_confirm(self, confirm: i8) -> Alias {
     return Alias{confirm: confirm , ..self};
   }
+  pub fn update_command(self, command: &str) -> Alias {
+    return Alias{command: command.to_string() , ..self};
+  }
+  pub fn update_storage_path(self, storage_path: PathBuf) -> Alias {
+    return Alias{storage_path: storage_path , ..self};
+  }
 
   /// If true, a confirmation prompt is shown whenever executing this alias
   pub fn get_confirmation_level(&self) -> i8 {return self.confirm;}
 
   /// Reads an alias file
   /// 
-  /// The format of an alias file is simple:
+  /// The name of the alias file must be the alias's name. Its contents are in TOML format:
+  /// ```toml
+  /// version = 1
+  /// command = "cd .."
+  /// description = "Go to the parent folder"
+  /// confirm = 0
+  /// ```
+  /// Only `version` and `command` are required. Alias files in the legacy format are also accepted;
+  /// see `Alias::read_legacy`.
+  pub fn read(alias: &str, path: &Path) -> Result<Alias, JumpcutError> {
+    let contents = fs::read_to_string(path)?;
+    return Alias::parse(alias, path, &contents);
+  }
+
+  /// Parses the contents of an alias file (see `Alias::read`)
+  ///
+  /// `path` is where the alias file is (or will be) stored.
+  pub fn parse(alias: &str, path: &Path, contents: &str) -> Result<Alias, JumpcutError> {
+    if is_legacy_format(contents) {
+      return Alias::read_legacy(alias, path, contents);
+    }
+
+    let file: AliasFile = toml::from_str(contents).map_err(|e| JumpcutError::InvalidFile(path.to_path_buf(), e.to_string()))?;
+    if file.version > ALIAS_FORMAT_VERSION {
+      return Err(JumpcutError::InvalidFile(path.to_path_buf(),
+        format!("format version {}; this version of Jumpcut only supports version {}", file.version, ALIAS_FORMAT_VERSION)));
+    }
+
+    let al = Alias::new(alias, &file.command, &file.description, file.confirm, path.to_path_buf());
+    return Ok(Alias{extra_fields: file.extra_fields, ..al});
+  }
+
+  /// Reads an alias file in the legacy (pre-TOML) format
+  /// 
+  /// The format of a legacy alias file is simple:
   /// - The name of the alias file must be the alias's name.
   /// - The contents of an alias file contains exactly 3 lines:
   ///   - the command that this alias expands to
   ///   - the alias's description (optional)
   ///   - the alias's options (e.g. "confirm=2")
-  pub fn read(alias: &str, path: &Path) -> Result<Alias, Error> {
-    let f = File::open(path)?;
+  fn read_legacy(alias: &str, path: &Path, contents: &str) -> Result<Alias, JumpcutError> {
     let path_buf = path.to_path_buf();
-    
-    let f = BufReader::new(f);
     let confirm_default = 0;
 
-    let lines:Result<Vec<String>, Error> = f.lines().collect();
-    let lines = lines.unwrap_or_default();
+    let lines: Vec<&str> = contents.lines().collect();
     return match lines.len() {
-      0 => Err(Error::new(ErrorKind::Other, format!("Empty or invalid alias file: {}", alias))),
-      1 => Ok(Alias::new(alias, &*lines[0], "", confirm_default, path_buf)),
-      2 => Ok(Alias::new(alias, &*lines[0], &*lines[1], confirm_default, path_buf)),
-      3 => {
-        let options = &lines[2];
-        let split = options.split("=");
-        let split_v: Vec<&str> = split.collect();
+      0 => Err(JumpcutError::InvalidFile(path_buf, "the alias file is empty".to_string())),
+      1 => Ok(Alias::new(alias, lines[0], "", confirm_default, path_buf)),
+      2 => Ok(Alias::new(alias, lines[0], lines[1], confirm_default, path_buf)),
+      _ => {
+        let split_v: Vec<&str> = lines[2].split('=').collect();
         if split_v.len()==2 && split_v[0] == "confirm" {
           let confirm = split_v[1].parse::<i8>().unwrap_or_default();
-          return Ok(Alias::new(alias.clone(), &*lines[0], &*lines[1], confirm, path_buf));
+          return Ok(Alias::new(alias, lines[0], lines[1], confirm, path_buf));
         } else {
-          return Ok(Alias::new(alias, &*lines[0], &*lines[1], confirm_default, path_buf));
+          return Ok(Alias::new(alias, lines[0], lines[1], confirm_default, path_buf));
         }
       }
-      _ => Ok(Alias::new(alias.clone(), &*lines[0], &*lines[1], confirm_default, path_buf)),
     };
   }
 
@@ -94,64 +155,174 @@ impl Alias {
 
   /// Write an `Alias` to file
   /// 
-  /// See alias::Alias::write() for information about the file format.
-  pub fn write(&self, path: &Path) -> std::io::Result<()> {
-    let data = self.command.clone() + "\n" + self.description.as_str() + "\nconfirm=" + &self.confirm.to_string();
-    return fs::write(path, data);
+  /// The structured format is always used; see alias::Alias::read() for information about the file format.
+  pub fn write(&self, path: &Path) -> Result<(), JumpcutError> {
+    fs::write(path, self.to_toml()?)?;
+    return Ok(());
+  }
+
+  /// Returns the contents of this alias's file (in the structured format)
+  pub fn to_toml(&self) -> Result<String, JumpcutError> {
+    let file = AliasFile {
+      version: ALIAS_FORMAT_VERSION,
+      command: self.command.clone(),
+      description: self.description.clone(),
+      confirm: self.confirm,
+      extra_fields: self.extra_fields.clone()
+    };
+    return toml::to_string(&file).map_err(|e| JumpcutError::InvalidFile(self.storage_path.clone(), e.to_string()));
+  }
+
+  /// Is this alias well-formed? If not, an error message is returned that can be shown to the user.
+  ///
+  /// The command must not be empty, the confirmation level must be 0, 1 or 2, and all ?[..] placeholders must be valid.
+  pub fn validate(&self) -> Result<(), String> {
+    if self.command.trim().is_empty() {
+      return Err("the command is empty".to_string());
+    }
+    if !(0..=2).contains(&self.confirm) {
+      return Err(format!("confirm must be 0, 1 or 2 (not {})", self.confirm));
+    }
+
+    let re = Regex::new(PLACEHOLDER_REGEX).unwrap();
+    let mut named_placeholders = 0;
+    for caps in re.captures_iter(&self.command) {
+      if let Some(spec) = caps.get(1) {
+        named_placeholders += 1;
+        if Param::parse(spec.as_str()).is_none() {
+          return Err(format!("invalid parameter placeholder {} (expected ?[!name:type|choice1,choice2=default], with a default that matches the type and choices)", &caps[0]));
+        }
+      }
+    }
+    if self.command.matches("?[").count() > named_placeholders {
+      return Err("a parameter placeholder is missing its closing \"]\"".to_string());
+    }
+    return Ok(());
   }
 
   /// "Execute" an alias using the given arguments
   /// 
-  /// Actually, the command to be executed is only printed to the console!
+  /// Actually, this only returns the command to be executed! (Multi-line scripts are returned as a whole.)
   /// The wrapper script that calls Jumpcut is responsible for executing this command.
   /// This is deliberate because programs are not allowed to mess with the user's shell environment,
   /// e.g. by changing environment variables or changing the working directory. However, any shell script
   /// launched via `source` is allowed to do this.
   pub fn execute(&self, args:HashMap<String, String>,
-                 missing_arg_handler:&dyn Fn(&Alias, &str) -> String) {
+                 missing_arg_handler:&MissingArgHandler) -> Result<String, JumpcutError> {
     // If the command contains "?pwd", this should be substituted for the current working directory
     let command_template = if self.command.ends_with("?pwd") {
-      let abs_pwd = absolute_path(&env::current_dir().unwrap());
+      let abs_pwd = absolute_path(&env::current_dir()?)?;
       let formatted_pwd = &format!("\"{}\"", abs_pwd)[..];
       self.command.replace("?pwd", formatted_pwd)
     } else {
       self.command.clone()
     };
 
-    let instantiated_command = self.fill_in_parameters(
-      command_template, args, missing_arg_handler, true);
-
-    // Simply print the result
-    println!("{}", instantiated_command);
+    return self.fill_in_parameters(command_template, args, missing_arg_handler, true);
   }
 
   /// Fill in the parameters of a command with `args`
   ///
-  /// If `args` doesn't the value for a parameter, `missing_arg_handler` is called.
+  /// Named parameters are written as ?[name] (see `param::Param` for the full syntax, including defaults, choices and types).
+  /// Positional parameters are written as ?1, ?2, .. (or ?[1], ?[2], ..);
+  /// their values are stored in `args` under the keys "1", "2", etc. The ?@ parameter expands to
+  /// all positional arguments (each as a separate word), and ?* expands to all of them as a single word.
+  /// Values are quoted for the current shell, unless the parameter is raw (?[!name]).
+  /// If `args` doesn't have a (valid) value for a parameter, its default value is used if we are doing a
+  /// `full_instantiation`; otherwise `missing_arg_handler` is called (with the reason why the value in `args` is
+  /// invalid, if there is one).
   /// If a parameter is intentially left blank, we'll pass a blank value if
   /// `full_instantiation` is true; otherwise we'll leave the parameter as-is.
   pub fn fill_in_parameters(&self, command: String, mut args:HashMap<String, String>,
-                            missing_arg_handler:&dyn Fn(&Alias, &str) -> String,
-                            full_instantiation: bool) -> String {
-    let re = Regex::new(r"\?\[([A-Za-z0-9_]*)\]").unwrap();
-    let out = re.replace_all(&command[..], |caps: &Captures|{
-      let key = caps.get(1).unwrap().as_str();
-      let val = match args.get(key) {
-        Some(v) => v,
-        None => {
-          let mut prompted_val = missing_arg_handler(self, key);
-          if prompted_val == "" && !full_instantiation {
-            prompted_val = caps.get(0).unwrap().as_str().to_string();
-          }
-          // Add it to args, so we won't ask again if this argument occurs more than once
-          args.insert(key.to_string(), prompted_val.clone());
-          return prompted_val;
-        }
-      };
-      return format!("{}", val);
+                            missing_arg_handler:&MissingArgHandler,
+                            full_instantiation: bool) -> Result<String, JumpcutError> {
+    let shell = Shell::detect();
+    let all_positional = positional_args(&args);
+    let re = Regex::new(PLACEHOLDER_REGEX).unwrap();
+    let mut out = String::new();
+    let mut last_end = 0;
+    for caps in re.captures_iter(&command) {
+      let whole = caps.get(0).unwrap();
+      out.push_str(&command[last_end..whole.start()]);
+      last_end = whole.end();
+      out.push_str(&self.fill_in_placeholder(&caps, &mut args, &all_positional, shell, missing_arg_handler, full_instantiation)?);
+    }
+    out.push_str(&command[last_end..]);
+    return Ok(out);
+  }
+
+  /// Returns the value that a single placeholder should be replaced with (see `fill_in_parameters`)
+  fn fill_in_placeholder(&self, caps: &Captures, args: &mut HashMap<String, String>, all_positional: &[String], shell: Shell,
+                         missing_arg_handler:&MissingArgHandler,
+                         full_instantiation: bool) -> Result<String, JumpcutError> {
+    let placeholder = caps.get(0).unwrap().as_str();
+    if let Some(all) = caps.get(3) {
+      return Ok(if all_positional.is_empty() && !full_instantiation {
+        placeholder.to_string()
+      } else if all.as_str() == "@" || all_positional.is_empty() {
+        // (Without any arguments, ?* expands to nothing like ?@, rather than to an empty argument)
+        all_positional.iter().map(|val| shell.quote(val)).collect::<Vec<String>>().join(" ")
+      } else {
+        shell.quote(&all_positional.join(" "))
+      });
+    }
+
+    let param = match (caps.get(1), caps.get(2)) {
+      (Some(spec), _) => match Param::parse(spec.as_str()) {
+        Some(x) => x,
+        None => return Ok(placeholder.to_string())
+      },
+      (None, Some(pos)) => Param::new(pos.as_str()),
+      _ => return Ok(placeholder.to_string())
+    };
+    let quote = |val: &str| if param.raw {val.to_string()} else {shell.quote(val)};
+
+    let mut rejection = None;
+    if let Some(val) = args.get(&param.name) {
+      match param.validate(val) {
+        Ok(()) => return Ok(quote(val)),
+        Err(msg) => rejection = Some(msg)
+      }
+    } else if full_instantiation {
+      if let Some(default) = &param.default {
+        return Ok(quote(default));
+      }
+    }
+
+    let prompted_val = missing_arg_handler(self, &param, rejection.as_deref())?;
+    // Add it to args, so we won't ask again if this argument occurs more than once
+    args.insert(param.name.to_string(), prompted_val.clone());
+    return Ok(if prompted_val.is_empty() && !full_instantiation {
+      placeholder.to_string()
+    } else {
+      quote(&prompted_val)
     });
+  }
+
+  /// Returns all (well-formed) parameters that occur in this alias's command, in order of appearance
+  pub fn params(&self) -> Vec<Param> {
+    let re = Regex::new(PLACEHOLDER_REGEX).unwrap();
+    return re.captures_iter(&self.command).filter_map(|caps| {
+      return match (caps.get(1), caps.get(2)) {
+        (Some(spec), _) => Param::parse(spec.as_str()),
+        (None, Some(pos)) => Some(Param::new(pos.as_str())),
+        _ => None
+      };
+    }).collect();
+  }
 
-    return out.to_string();
+  /// Returns a one-line preview of this alias's command
+  /// 
+  /// For multi-line commands, this is the first line, followed by the number of lines that were left out.
+  pub fn command_preview(&self) -> String {
+    let mut lines = self.command.lines();
+    let first_line = lines.next().unwrap_or_default();
+    let remaining = lines.count();
+    return if remaining == 0 {
+      first_line.to_string()
+    } else {
+      format!("{} ... (+{} more lines)", first_line, remaining)
+    };
   }
 
   pub fn to_string(&self, width: usize) -> String {
@@ -163,14 +334,41 @@ impl Alias {
     };
 
     let styled_alias = accent_style().apply_to(&self.alias);
-    if self.description == "" {
-      return format!("{: <w$}  {} {}", styled_alias, self.command, flags, w=width);
+    if self.description.is_empty() {
+      return format!("{: <w$}  {} {}", styled_alias, self.command_preview(), flags, w=width);
     } else {
-      return format!("{: <w$}  {} {}\n{: <w$}  {}", styled_alias, self.command, flags, "", self.description, w=width);
+      return format!("{: <w$}  {} {}\n{: <w$}  {}", styled_alias, self.command_preview(), flags, "", self.description, w=width);
     }
   }
 }
 
+/// Returns the values of all positional arguments (stored under the keys "1", "2", ..) in `args`, in order
+fn positional_args(args: &HashMap<String, String>) -> Vec<String> {
+  let mut positional: Vec<(usize, &String)> = args.iter()
+    .filter_map(|(key, val)| key.parse::<usize>().ok().map(|pos| (pos, val)))
+    .collect();
+  positional.sort();
+  return positional.into_iter().map(|(_, val)| val.clone()).collect();
+}
+
+/// Is the given alias file content in the legacy (pre-TOML) format?
+///
+/// Legacy alias files have 1 to 3 lines: the command, an optional description and an optional `confirm=N` line.
+/// (Files in the structured format that are missing their `version` are not legacy files, but invalid ones.)
+pub fn is_legacy_format(contents: &str) -> bool {
+  let is_structured = match contents.parse::<toml::Value>() {
+    Ok(toml::Value::Table(table)) => table.contains_key("version") || table.contains_key("command"),
+    _ => false
+  };
+  let lines: Vec<&str> = contents.lines().collect();
+  let confirm_line = Regex::new(r"^confirm=-?[0-9]+$").unwrap();
+  return !is_structured && match lines.len() {
+    1 | 2 => true,
+    3 => confirm_line.is_match(lines[2].trim()),
+    _ => false
+  };
+}
+
 impl std::fmt::Display for Alias {
   fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
     return write!(f, "{}", self.to_string(self.alias.len()));   
@@ -0,0 +1,56 @@
+use crate::alias::Alias;
+use crate::error::JumpcutError;
+use crate::resolve::{rank, Candidate};
+use crate::stats::UsageStats;
+use crate::store::AliasStore;
+
+// What the full-screen alias browser (`j ui`) shows, independent of the terminal it is drawn in
+
+/// Returns all aliases in `stores` that match the text typed in the browser's filter, best matches first
+///
+/// Each word of the filter has to match (just like the parts of a query when executing an alias).
+pub fn filter_aliases<S: AliasStore>(stores: &[S], filter: &str, stats: &UsageStats) -> Result<Vec<Candidate>, JumpcutError> {
+  let query: Vec<String> = filter.split_whitespace().map(|x| x.to_string()).collect();
+  return rank(stores, &query, stats);
+}
+
+/// Returns the lines of the preview pane, describing the given alias (which is in the store named `store_name`)
+pub fn preview_lines(al: &Alias, store_name: &str) -> Vec<String> {
+  let mut lines = vec!["Command:".to_string()];
+  lines.extend(al.get_command().lines().map(|line| format!("  {}", line)));
+  lines.push("".to_string());
+  let description = if al.get_description().is_empty() {"-"} else {al.get_description()};
+  lines.push(format!("Description:  {}", description));
+  lines.push(format!("Confirmation: {}", confirmation_name(al.get_confirmation_level())));
+  lines.push(format!("Store:        {}", store_name));
+
+  let params = al.params();
+  if params.is_empty() {
+    lines.push("Parameters:   none".to_string());
+  } else {
+    lines.push("Parameters:".to_string());
+    lines.extend(params.iter().map(|param| format!("  {}", param.summary())));
+  }
+  return lines;
+}
+
+/// Describes a confirmation level, e.g. "y/n confirmation"
+pub fn confirmation_name(level: i8) -> &'static str {
+  return match level {
+    0 => "none",
+    1 => "y/n confirmation",
+    _ => "explicit confirmation"
+  };
+}
+
+/// Returns the index of the first alias to show, so the `selected` one is visible in a list of `page` rows
+///
+/// `scroll` is the index of the first alias that was shown so far; the list only scrolls if needed.
+pub fn scroll_position(selected: usize, scroll: usize, page: usize) -> usize {
+  if selected < scroll {
+    return selected;
+  } else if selected >= scroll + page.max(1) {
+    return selected + 1 - page.max(1);
+  }
+  return scroll;
+}
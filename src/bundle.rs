@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::alias::Alias;
+use crate::error::JumpcutError;
+use crate::store::{AliasStore, validate_alias_name};
+
+/// Version of the bundle format that is written by `Bundle::to_json`
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+/// A collection of aliases in a single (JSON) file, to move aliases between machines
+///
+/// Each alias contains its name, and all fields of its alias file (see `Alias::read`), e.g.:
+/// ```json
+/// {
+///   "jumpcut_bundle": 1,
+///   "aliases": [
+///     {"name": "up", "version": 1, "command": "cd ..", "description": "Go up", "confirm": 0}
+///   ]
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Bundle {
+  jumpcut_bundle: u32,
+  pub aliases: Vec<BundleAlias>
+}
+
+/// An alias in a bundle
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BundleAlias {
+  pub name: String,
+  #[serde(flatten)]
+  fields: BTreeMap<String, toml::Value> // the fields of the alias file, including any we don't know about (yet)
+}
+
+/// What to do when importing an alias, if the store already contains a different alias with the same name
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum OnConflict {
+  /// Keep the existing alias
+  Skip,
+  /// Replace the existing alias
+  Overwrite,
+  /// Import the alias under another name (by adding a number to its name)
+  Rename,
+  /// Ask the user to choose one of the other options
+  Ask
+}
+
+impl OnConflict {
+  pub fn from_name(name: &str) -> Option<OnConflict> {
+    return match name {
+      "skip" => Some(OnConflict::Skip),
+      "overwrite" => Some(OnConflict::Overwrite),
+      "rename" => Some(OnConflict::Rename),
+      "ask" => Some(OnConflict::Ask),
+      _ => None
+    };
+  }
+}
+
+/// What importing a bundle does with one of its aliases
+#[derive(Clone)]
+pub enum ImportAction {
+  /// Add a new alias
+  Add(Alias),
+  /// Replace an existing alias
+  Change(Alias),
+  /// Don't import the alias with the given name (reason)
+  Skip(String, String)
+}
+
+impl Bundle {
+  /// Creates a bundle containing the given aliases of `store`
+  pub fn export<S: AliasStore>(store: &S, aliases: &[String]) -> Result<Bundle, JumpcutError> {
+    let mut bundle = Bundle{jumpcut_bundle: BUNDLE_FORMAT_VERSION, aliases: Vec::new()};
+    for name in aliases {
+      let al = store.get(name)?.ok_or_else(|| JumpcutError::NotFound(name.clone()))?;
+      // (Legacy alias files are converted to the structured format)
+      let fields = toml::from_str(&al.to_toml()?).map_err(|e| JumpcutError::InvalidFile(store.alias_file(name), e.to_string()))?;
+      bundle.aliases.push(BundleAlias{name: name.clone(), fields: fields});
+    }
+    return Ok(bundle);
+  }
+
+  /// Parses the contents of a bundle file; `path` is only used in error messages
+  pub fn parse(path: &Path, contents: &str) -> Result<Bundle, JumpcutError> {
+    let bundle: Bundle = serde_json::from_str(contents).map_err(|e| JumpcutError::InvalidFile(path.to_path_buf(), e.to_string()))?;
+    if bundle.jumpcut_bundle > BUNDLE_FORMAT_VERSION {
+      return Err(JumpcutError::InvalidFile(path.to_path_buf(),
+        format!("bundle format version {}; this version of Jumpcut only supports version {}", bundle.jumpcut_bundle, BUNDLE_FORMAT_VERSION)));
+    }
+    return Ok(bundle);
+  }
+
+  /// Returns this bundle in JSON format
+  pub fn to_json(&self) -> Result<String, JumpcutError> {
+    return serde_json::to_string_pretty(self).map_err(|e| JumpcutError::InvalidArgument(format!("could not export the aliases: {}", e)));
+  }
+
+  /// Determines what importing this bundle into `store` would do, without changing anything
+  ///
+  /// The name of each imported alias is preceded by `prefix`. If the store already contains an alias with the same
+  /// name (which isn't identical), `on_conflict` decides what happens. For `OnConflict::Ask`, `conflict_handler` is
+  /// called with the alias's name to choose one of the other options. (If it returns `OnConflict::Ask` again, the alias
+  /// is skipped, as the user would need to be asked.)
+  pub fn plan_import<S: AliasStore>(&self, store: &S, prefix: &str, on_conflict: OnConflict,
+      conflict_handler: &dyn Fn(&str) -> Result<OnConflict, JumpcutError>) -> Result<Vec<ImportAction>, JumpcutError> {
+    let mut actions = Vec::new();
+    let mut imported: HashSet<String> = HashSet::new();
+    for entry in self.aliases.iter() {
+      let name = format!("{}{}", prefix, entry.name);
+      if imported.contains(&name) {
+        actions.push(ImportAction::Skip(name, "it occurs more than once in the bundle".to_string()));
+        continue;
+      }
+      let al = match entry.to_alias(&name, &store.alias_file(&name)) {
+        Ok(al) => al,
+        Err(reason) => {
+          actions.push(ImportAction::Skip(name, reason));
+          continue;
+        }
+      };
+
+      let existing = match store.get(&name) {
+        Ok(existing) => existing,
+        Err(e) => {
+          actions.push(ImportAction::Skip(name, format!("the existing alias can't be read: {}", e)));
+          continue;
+        }
+      };
+      let action = match existing {
+        None => ImportAction::Add(al),
+        Some(existing) if existing.to_toml()? == al.to_toml()? => ImportAction::Skip(name.clone(), "unchanged".to_string()),
+        Some(_) => {
+          let choice = if on_conflict == OnConflict::Ask {conflict_handler(&name)?} else {on_conflict};
+          match choice {
+            OnConflict::Overwrite => ImportAction::Change(al),
+            OnConflict::Rename => {
+              let new_name = (2..).map(|i| format!("{}-{}", name, i))
+                .find(|new_name| !store.contains(new_name) && !imported.contains(new_name))
+                .unwrap_or_default();
+              imported.insert(new_name.clone());
+              ImportAction::Add(entry.to_alias(&new_name, &store.alias_file(&new_name)).map_err(JumpcutError::InvalidArgument)?)
+            },
+            OnConflict::Ask => ImportAction::Skip(name.clone(), "an alias with that name already exists; you'll be asked what to do".to_string()),
+            OnConflict::Skip => ImportAction::Skip(name.clone(), "an alias with that name already exists".to_string())
+          }
+        }
+      };
+      imported.insert(name);
+      actions.push(action);
+    }
+    return Ok(actions);
+  }
+}
+
+impl BundleAlias {
+  /// Converts this bundle entry to a (valid) alias named `name`, stored in `path`
+  ///
+  /// If it isn't valid, an error message is returned that can be shown to the user.
+  fn to_alias(&self, name: &str, path: &Path) -> Result<Alias, String> {
+    validate_alias_name(name).map_err(|e| e.to_string())?;
+    if !self.fields.contains_key("version") {
+      return Err("`version` is missing".to_string());
+    }
+    // (As a TOML value, any nested tables are written after the other fields)
+    let table = toml::Value::Table(self.fields.clone().into_iter().collect());
+    let contents = toml::to_string(&table).map_err(|e| e.to_string())?;
+    let al = Alias::parse(name, path, &contents).map_err(|e| match e {
+      JumpcutError::InvalidFile(_, reason) => reason,
+      e => e.to_string()
+    })?;
+    al.validate()?;
+    return Ok(al);
+  }
+}
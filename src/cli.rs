@@ -0,0 +1,101 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use dialoguer::console::style;
+use dialoguer::theme::ColorfulTheme;
+use jumpcut::error::JumpcutError;
+use jumpcut::utils::env_flag;
+
+pub const JUMPCUT_AUTO_PICK_ENV_VAR: &str = "JUMPCUT_AUTO_PICK";
+pub const JUMPCUT_NONINTERACTIVE_ENV_VAR: &str = "JUMPCUT_NONINTERACTIVE";
+
+/// Set if prompts were disabled with the --no-input flag
+static NO_INPUT: AtomicBool = AtomicBool::new(false);
+
+/// Never prompt the user for input (for the remainder of this process)
+pub fn disable_input() {
+    NO_INPUT.store(true, Ordering::Relaxed);
+}
+
+/// Can we prompt the user for input?
+/// 
+/// Not if prompts are disabled (via --no-input or JUMPCUT_NONINTERACTIVE), or if stdin isn't connected to a terminal.
+pub fn is_interactive() -> bool {
+    return !NO_INPUT.load(Ordering::Relaxed) && !env_flag(JUMPCUT_NONINTERACTIVE_ENV_VAR)
+        && std::io::stdin().is_terminal();
+}
+
+/// Returns a theme that can be used in dialoguer's widgets
+pub fn dialoguer_theme() -> ColorfulTheme {
+    ColorfulTheme {
+        active_item_prefix: style(">".to_string()).for_stderr(),
+        ..ColorfulTheme::default()
+    }
+}
+
+/// Check the length of the given argument list
+/// 
+/// If the length is not as expected, the usage message is printed and an error is returned.
+pub fn args_ok(args: &[String], num: usize) -> Result<(), JumpcutError> {
+    if args.len() >= num + 2 {
+        return Ok(());
+    }
+    usage();
+    return Err(JumpcutError::InvalidArgument(format!("incorrect number of arguments; {} expected", num)));
+}
+
+/// Prints an error message to stderr
+/// 
+/// Only meant for errors that Jumpcut can recover from; otherwise, return a `JumpcutError` instead.
+pub fn error(err: &str) {
+    eprintln!("Error: {}", err);
+}
+
+/// Prints usage message
+pub fn usage() {
+    print!("
+    Jumpcut usage:
+
+    j ALIAS                       Execute the alias called ALIAS (also works by only entering parts of its name)
+    j ALIAS --PARAM=VAL -- ARGS   Execute ALIAS, filling in ?[PARAM] with VAL, and ?1, ?2, .. (or ?@ for all) with ARGS
+    j --no-input ...              Never prompt for input; fail instead (e.g. if a parameter value or confirmation is missing)
+    j list [SEARCH]               List all aliases (containing SEARCH in their name)
+    j ui                          Browse, filter, execute and edit aliases in a full-screen interface
+
+    j add ALIAS CMD               Adds a new alias, which executes the given command (parameter syntax: ?[PARAM:TYPE|CHOICES=DEFAULT] or ?1, ?2, ..)
+    j add ALIAS -                 Adds a new alias, which executes the (multi-line) script read from stdin
+    j add ALIAS --file FILE       Adds a new alias, which executes the (multi-line) script in FILE
+    j add --project ALIAS CMD     Adds a new alias to the project in this directory (in its .jumpcut folder)
+    j addwd ALIAS CMD             Adds a new alias, which executes the given command, always from this working directory
+    j addpath ALIAS [PATH]        Adds a new alias, which navigates to the given path (default path: \".\")
+    j addlast ALIAS [N]           Creates ALIAS for the last (N) command(s) in your shell's history, asking which one(s) if interactive
+    j addshr ALIAS CMD            Identical to `j add`, but stores the alias in JUMPCUT_SHARED_PATH
+
+    j edit ALIAS                  Opens ALIAS (command, description, confirmation, ..) in $VISUAL or $EDITOR
+    j desc ALIAS DESC             Sets the description of ALIAS to DESC
+    j confirm ALIAS 0|1|2         Set alias confirmation prompt (0: none ; 1: y/n confirmation ; 2: explicit confirmation)
+    j cp ALIAS1 ALIAS2            Copies ALIAS1 to ALIAS2, and optionally fill in any parameters
+    j cpshr ALIAS1 ALIAS2         Identical to `j cp` , but stores the copied alias in JUMPCUT_SHARED_PATH
+    j mv ALIAS NEW_NAME           Renames ALIAS to NEW_NAME
+    j promote ALIAS               Moves ALIAS to JUMPCUT_SHARED_PATH
+    j demote ALIAS                Moves ALIAS from JUMPCUT_SHARED_PATH back to your own aliases
+    j rm ALIAS                    Removes ALIAS
+    j trust ALIAS                 Approves the current version of a shared/project ALIAS, so it runs without asking
+    j sync [STORE]                Commits, pulls and pushes the aliases in the shared folder (or STORE), if it's a git working copy
+    j export [SEARCH] [--store STORE]   Prints all aliases (containing SEARCH in their name) as a JSON bundle
+    j import FILE [--prefix PREFIX] [--on-conflict skip|overwrite|rename|ask] [--store STORE] [--dry-run]
+                                  Imports the aliases in a bundle (or \"-\" for stdin); --dry-run only shows what would change
+    j import-shell SHELL FILE [--store STORE]
+                                  Creates aliases from the aliases, functions and abbreviations in a config file of SHELL
+                                  (bash, zsh, fish or powershell), e.g. ~/.bashrc
+    j suggest [--min-count N] [--top N]
+                                  Suggests aliases for commands/folders you often use (at least N times), based on your shell's history
+    j import-dirs zoxide|z|autojump|fasd [--top N] [--min-score S] [--file FILE]
+                                  Adds aliases for your most-used directories, according to the database of zoxide, z, ..
+    j init SHELL [--name NAME]    Prints the wrapper function for SHELL (bash, zsh, fish, powershell or nushell), named NAME (default: j)
+    j completions SHELL [--name NAME]  Prints the tab-completion script for SHELL (already included in `j init`)
+    j stats                       Lists all aliases, from most to least used
+    j migrate                     Converts all alias files in the legacy format to the current format (a backup is made first)
+
+    Reference documentation: https://github.com/timmolderez/jumpcut-aliases/blob/master/README.md
+    ")
+}
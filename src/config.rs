@@ -0,0 +1,109 @@
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use crate::error::JumpcutError;
+use crate::paths::*;
+use crate::utils::*;
+
+/// Environment variable with a list of alias stores, separated like PATH (`:` or `;` on Windows)
+pub const JUMPCUT_PATH_ENV_VAR: &str = "JUMPCUT_PATH";
+
+/// What to do if several stores contain an alias with the same name
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictRule {
+  /// Use the alias in the store with the highest precedence
+  Shadow,
+  /// Ask the user which alias to use
+  Ask
+}
+
+impl Default for ConflictRule {
+  fn default() -> ConflictRule {
+    return ConflictRule::Shadow;
+  }
+}
+
+/// An alias store, as configured by the user
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub struct StoreConfig {
+  pub name: String,
+  pub path: PathBuf,
+  #[serde(default)]
+  pub read_only: bool,
+  #[serde(default)]
+  pub priority: i32 // stores with a higher priority take precedence
+}
+
+/// Jumpcut's configuration file, e.g.:
+/// ```toml
+/// on_conflict = "shadow"
+///
+/// [[store]]
+/// name = "team"
+/// path = "/mnt/team/aliases"
+/// read_only = true
+/// priority = 10
+/// ```
+#[derive(Deserialize, Default, Debug)]
+pub struct Config {
+  #[serde(default)]
+  pub on_conflict: ConflictRule,
+  #[serde(default, rename = "store")]
+  pub stores: Vec<StoreConfig>
+}
+
+impl Config {
+  /// Reads the configuration file; if it doesn't exist, the default configuration is returned
+  pub fn load(path: &Path) -> Result<Config, JumpcutError> {
+    if !path.exists() {
+      return Ok(Config::default());
+    }
+    let contents = fs::read_to_string(path)?;
+    return toml::from_str(&contents).map_err(|e| JumpcutError::InvalidFile(path.to_path_buf(), e.to_string()));
+  }
+
+  /// Reads the configuration file at `config_path()`, and applies the JUMPCUT_PATH environment variable (if set)
+  pub fn load_default() -> Result<Config, JumpcutError> {
+    let mut config = Config::load(&config_path())?;
+    if let Some(value) = env::var_os(JUMPCUT_PATH_ENV_VAR) {
+      config.stores = parse_store_list(&value)?;
+    }
+    return Ok(config);
+  }
+}
+
+/// Parses a list of stores, as in the JUMPCUT_PATH environment variable
+///
+/// Each entry is a folder, optionally preceded by the store's name (and ",ro" if it is read-only), e.g.
+/// `team,ro=/mnt/team/aliases:/home/me/more-aliases`. All stores get the default priority, so they take precedence
+/// in the order they are listed. Stores without a name are named after their folder.
+pub fn parse_store_list(value: &OsStr) -> Result<Vec<StoreConfig>, JumpcutError> {
+  let mut stores = Vec::new();
+  for entry in env::split_paths(value) {
+    let entry = osstr_to_string(entry.as_os_str());
+    if entry.is_empty() {
+      continue;
+    }
+    let (options, path) = match entry.split_once('=') {
+      Some((options, path)) => (options, PathBuf::from(path)),
+      None => ("", PathBuf::from(&entry))
+    };
+    let mut options = options.split(',');
+    let name = match options.next().filter(|name| !name.is_empty()) {
+      Some(name) => name.to_string(),
+      None => osstr_to_string(path.file_name().unwrap_or_else(|| path.as_os_str()))
+    };
+    let mut read_only = false;
+    for option in options {
+      match option {
+        "ro" => read_only = true,
+        _ => return Err(JumpcutError::InvalidArgument(format!("unknown option {:?} in {} entry {:?}", option, JUMPCUT_PATH_ENV_VAR, entry)))
+      }
+    }
+    stores.push(StoreConfig{name: name, path: path, read_only: read_only, priority: 0});
+  }
+  return Ok(stores);
+}
@@ -0,0 +1,188 @@
+use std::convert::TryInto;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::error::JumpcutError;
+
+/// Tools that keep track of frequently-used directories, whose databases can be imported
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum DirTool {
+  Zoxide,
+  Z,
+  Autojump,
+  Fasd
+}
+
+/// A directory in a tool's database
+#[derive(Clone, PartialEq, Debug)]
+pub struct DirEntry {
+  pub path: PathBuf,
+  pub score: f64 // how frequently/recently the directory is used; only comparable within the same database
+}
+
+impl DirTool {
+  /// Looks up a tool by name (e.g. "zoxide")
+  pub fn from_name(name: &str) -> Option<DirTool> {
+    return match name.to_lowercase().as_ref() {
+      "zoxide" => Some(DirTool::Zoxide),
+      "z" => Some(DirTool::Z),
+      "autojump" => Some(DirTool::Autojump),
+      "fasd" => Some(DirTool::Fasd),
+      _ => None
+    };
+  }
+
+  /// The name of this tool, as accepted by `DirTool::from_name`
+  pub fn name(&self) -> &'static str {
+    return match self {
+      DirTool::Zoxide => "zoxide",
+      DirTool::Z => "z",
+      DirTool::Autojump => "autojump",
+      DirTool::Fasd => "fasd"
+    };
+  }
+
+  /// Returns where this tool stores its database by default (taking into account the tool's own environment variables)
+  pub fn default_database(&self) -> Option<PathBuf> {
+    let from_env = |name: &str| env::var_os(name).filter(|x| !x.is_empty()).map(PathBuf::from);
+    return match self {
+      DirTool::Zoxide => from_env("_ZO_DATA_DIR").or_else(|| dirs::data_local_dir().map(|dir| dir.join("zoxide")))
+        .map(|dir| dir.join("db.zo")),
+      DirTool::Z => from_env("_Z_DATA").or_else(|| dirs::home_dir().map(|home| home.join(".z"))),
+      DirTool::Autojump => if cfg!(target_os = "macos") {
+        dirs::home_dir().map(|home| home.join("Library").join("autojump").join("autojump.txt"))
+      } else {
+        dirs::data_dir().map(|dir| dir.join("autojump").join("autojump.txt"))
+      },
+      DirTool::Fasd => from_env("_FASD_DATA").or_else(|| dirs::home_dir().map(|home| home.join(".fasd")))
+    };
+  }
+
+  /// Reads this tool's database, and returns its entries from highest to lowest score
+  pub fn read_database(&self, path: &Path) -> Result<Vec<DirEntry>, JumpcutError> {
+    let invalid = |reason: String| JumpcutError::InvalidFile(path.to_path_buf(), reason);
+    let mut entries = match self {
+      DirTool::Zoxide => parse_zoxide(&fs::read(path)?).map_err(invalid)?,
+      DirTool::Z | DirTool::Fasd => parse_z(&fs::read_to_string(path)?).map_err(invalid)?,
+      DirTool::Autojump => parse_autojump(&fs::read_to_string(path)?).map_err(invalid)?
+    };
+    entries.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    return Ok(entries);
+  }
+}
+
+/// Parses a z or fasd database, which has a `PATH|RANK|TIMESTAMP` line for each entry
+fn parse_z(contents: &str) -> Result<Vec<DirEntry>, String> {
+  let mut entries = Vec::new();
+  for (i, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+    // (Paths may contain a `|`, so the fields are split off from the end)
+    let mut fields = line.rsplitn(3, '|');
+    let (_timestamp, rank, path) = match (fields.next(), fields.next(), fields.next()) {
+      (Some(timestamp), Some(rank), Some(path)) => (timestamp, rank, path),
+      _ => return Err(format!("line {} is not of the form PATH|RANK|TIMESTAMP", i + 1))
+    };
+    let score = rank.trim().parse().map_err(|_| format!("invalid rank {:?} on line {}", rank, i + 1))?;
+    entries.push(DirEntry{path: PathBuf::from(path), score: score});
+  }
+  return Ok(entries);
+}
+
+/// Parses an autojump database, which has a `WEIGHT<tab>PATH` line for each entry
+fn parse_autojump(contents: &str) -> Result<Vec<DirEntry>, String> {
+  let mut entries = Vec::new();
+  for (i, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+    let (weight, path) = line.split_once('\t').ok_or_else(|| format!("line {} is not of the form WEIGHT<tab>PATH", i + 1))?;
+    let score = weight.trim().parse().map_err(|_| format!("invalid weight {:?} on line {}", weight, i + 1))?;
+    entries.push(DirEntry{path: PathBuf::from(path), score: score});
+  }
+  return Ok(entries);
+}
+
+/// Parses a zoxide database (format version 3)
+///
+/// This is a bincode-encoded version number (u32), followed by a list of (path, rank, last accessed) entries. All
+/// integers are little-endian, and lengths are u64s.
+fn parse_zoxide(data: &[u8]) -> Result<Vec<DirEntry>, String> {
+  let mut reader = BincodeReader{data: data, pos: 0};
+  let version = u32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+  if version != 3 {
+    return Err(format!("unsupported zoxide database version {}", version));
+  }
+  let count = reader.read_u64()?;
+  let mut entries = Vec::new();
+  for _ in 0..count {
+    let len = reader.read_u64()? as usize;
+    let path = String::from_utf8(reader.take(len)?.to_vec()).map_err(|e| e.to_string())?;
+    let rank = f64::from_le_bytes(reader.take(8)?.try_into().unwrap());
+    let _last_accessed = reader.read_u64()?;
+    entries.push(DirEntry{path: PathBuf::from(path), score: rank});
+  }
+  return Ok(entries);
+}
+
+struct BincodeReader<'a> {
+  data: &'a [u8],
+  pos: usize
+}
+
+impl<'a> BincodeReader<'a> {
+  fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+    if self.data.len() - self.pos < len {
+      return Err("unexpected end of file".to_string());
+    }
+    let bytes = &self.data[self.pos..self.pos + len];
+    self.pos += len;
+    return Ok(bytes);
+  }
+
+  fn read_u64(&mut self) -> Result<u64, String> {
+    return Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()));
+  }
+}
+
+/// Returns the command that navigates to the directory `dir` (like the aliases created by `j addpath`)
+///
+/// Returns None if `dir` contains characters that some shells would expand or unescape in a double-quoted string.
+pub fn cd_command(dir: &str) -> Option<String> {
+  // (Backslashes are path separators on Windows, where PowerShell doesn't treat them as escapes)
+  if dir.contains(|c: char| "\"$`".contains(c) || (c == '\\' && !cfg!(windows)) || c.is_control()) {
+    return None;
+  }
+  return Some(format!("cd \"{}\"", dir));
+}
+
+/// Proposes an alias name for each directory, derived from its name
+///
+/// Names for which `is_taken` returns true (e.g. existing aliases) are avoided by including the parent directory's
+/// name (e.g. `api-docs` for `~/api/docs`), or otherwise by adding a number. Directories whose name can't be used
+/// (e.g. the root directory) are left out.
+pub fn propose_names(entries: &[DirEntry], is_taken: &dyn Fn(&str) -> bool) -> Vec<(String, DirEntry)> {
+  let mut proposals: Vec<(String, DirEntry)> = Vec::new();
+  for entry in entries {
+    let base = match entry.path.file_name().map(|name| to_alias_name(&name.to_string_lossy())) {
+      Some(base) if !base.is_empty() => base,
+      _ => continue
+    };
+    let parent = entry.path.parent().and_then(|parent| parent.file_name())
+      .map(|name| to_alias_name(&name.to_string_lossy()))
+      .filter(|parent| !parent.is_empty());
+    let mut candidates = vec![base.clone()];
+    candidates.extend(parent.map(|parent| format!("{}-{}", parent, base)));
+    candidates.extend((2..).map(|i| format!("{}-{}", base, i)).take(100));
+
+    let available = |name: &String| !is_taken(name) && !proposals.iter().any(|(proposed, _)| proposed == name);
+    if let Some(name) = candidates.into_iter().find(available) {
+      proposals.push((name, entry.clone()));
+    }
+  }
+  return proposals;
+}
+
+/// Turns a directory name into something that is easy to type, e.g. "My Project" into "my-project"
+fn to_alias_name(dir_name: &str) -> String {
+  let name: String = dir_name.to_lowercase().chars()
+    .map(|c| if c.is_alphanumeric() || c == '_' || c == '-' || c == '.' {c} else {'-'})
+    .collect();
+  let parts: Vec<&str> = name.split('-').filter(|part| !part.is_empty()).collect();
+  return parts.join("-").trim_start_matches('.').to_string();
+}
@@ -0,0 +1,158 @@
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::time::{SystemTime, UNIX_EPOCH};
+use jumpcut::alias::Alias;
+use jumpcut::error::JumpcutError;
+use jumpcut::store::{AliasStore, FsStore, validate_alias_name};
+use crate::cli::*;
+use crate::{is_reserved_keyword, load_alias, remember_trusted, update_stats, update_trust};
+
+/// Prefix of the lines that explain why the edited alias is invalid (removed again before re-opening the editor)
+const ERROR_BANNER_PREFIX: &str = "# ERROR: ";
+
+const EDIT_HEADER: &str = "# Edit the alias below, then save and close this file.
+# Changing `name` renames the alias. Parameters: ?[!name:type|choice1,choice2=default] or ?1, ?2, ..
+# confirm: 0 = no confirmation ; 1 = y/n confirmation ; 2 = must type the alias name
+";
+
+/// Open an existing alias in the user's editor ($VISUAL or $EDITOR), and save the changes
+///
+/// The alias is shown in the structured file format, with an extra `name` field. If the result is invalid,
+/// the editor is re-opened with an explanation at the top of the file (unless prompts are disabled);
+/// closing it without any further changes discards the edits. (As the user has seen the entire alias, the saved
+/// version is trusted; see `check_trust`.)
+pub fn edit_alias(alias: &str) -> Result<(), JumpcutError> {
+  let (store, al) = load_alias(alias)?;
+  let editor = editor_command();
+  let tmp_path = create_temp_file()?;
+  let original = format!("{}name = {}\n{}", EDIT_HEADER, toml::Value::String(alias.to_string()), al.to_toml()?);
+
+  let mut contents = original.clone();
+  loop {
+    fs::write(&tmp_path, &contents)?;
+    run_editor(&editor, &tmp_path)?;
+    let edited = fs::read_to_string(&tmp_path)?;
+    if edited == original {
+      fs::remove_file(&tmp_path)?;
+      println!("No changes made to {}", alias);
+      return Ok(());
+    }
+    if edited == contents {
+      // (The user closed the editor without fixing the error)
+      fs::remove_file(&tmp_path)?;
+      return Err(JumpcutError::Cancelled);
+    }
+
+    match parse_edited_alias(&edited, alias, &store) {
+      Ok(edited_al) => {
+        fs::remove_file(&tmp_path)?;
+        return save_edited_alias(alias, edited_al, &store);
+      },
+      Err(msg) => {
+        if !is_interactive() {
+          // (The edited file is kept, so the changes aren't lost)
+          return Err(JumpcutError::InvalidFile(tmp_path, msg));
+        }
+        let without_banner: Vec<&str> = edited.lines().filter(|line| !line.starts_with(ERROR_BANNER_PREFIX)).collect();
+        contents = format!("{}{}\n{}\n", ERROR_BANNER_PREFIX, msg, without_banner.join("\n"));
+      }
+    }
+  }
+}
+
+/// Parses and validates the contents of the edited file
+///
+/// Returns the edited alias, or an error message that can be shown to the user.
+fn parse_edited_alias(contents: &str, old_name: &str, store: &FsStore) -> Result<Alias, String> {
+  let mut table = match contents.parse::<toml::Value>() {
+    Ok(toml::Value::Table(table)) => table,
+    Ok(_) => return Err("expected a TOML table".to_string()),
+    Err(e) => return Err(format!("invalid TOML: {}", e))
+  };
+
+  let name = match table.remove("name") {
+    Some(toml::Value::String(name)) => name,
+    Some(_) => return Err("`name` must be a string".to_string()),
+    None => return Err("`name` is missing".to_string())
+  };
+  validate_alias_name(&name).map_err(|e| e.to_string())?;
+  if is_reserved_keyword(&name) {
+    return Err(format!("{:?} cannot be used as an alias name; it is a reserved keyword", name));
+  }
+  if name != old_name && store.contains(&name) {
+    return Err(format!("an alias named {:?} already exists", name));
+  }
+  if !table.contains_key("version") {
+    return Err("`version` is missing".to_string());
+  }
+
+  let file_contents = toml::to_string(&table).map_err(|e| e.to_string())?;
+  let al = Alias::parse(&name, &store.alias_file(&name), &file_contents).map_err(|e| match e {
+    JumpcutError::InvalidFile(_, reason) => reason,
+    e => e.to_string()
+  })?;
+  al.validate()?;
+  return Ok(al);
+}
+
+/// Stores the edited alias, renaming it if its name was changed
+fn save_edited_alias(old_name: &str, al: Alias, store: &FsStore) -> Result<(), JumpcutError> {
+  let new_name = al.get_alias().to_string();
+  if new_name == old_name {
+    store.put(&al)?;
+    remember_trusted(&new_name, store).ok();
+    println!("Updated {}", new_name);
+  } else {
+    store.put(&al)?;
+    store.remove(old_name)?;
+    update_stats(|stats| stats.rename(&store.alias_file(old_name), &store.alias_file(&new_name))).ok();
+    update_trust(|db| db.remove(&store.alias_file(old_name))).ok();
+    remember_trusted(&new_name, store).ok();
+    println!("Updated {} and renamed it to {}", old_name, new_name);
+  }
+  return Ok(());
+}
+
+/// Creates a new, empty file in the temporary folder, in which the alias can be edited
+///
+/// The file is always newly created (and only readable by the user), so we never end up writing to a file or symlink
+/// that someone else put there. Its name is hard to guess, and another one is tried if it already exists.
+fn create_temp_file() -> Result<PathBuf, JumpcutError> {
+  for attempt in 0..100 {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|x| x.subsec_nanos()).unwrap_or_default();
+    let path = env::temp_dir().join(format!("jumpcut-edit-{}-{:x}{}.toml", process::id(), nanos, attempt));
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    match options.open(&path) {
+      Ok(_) => return Ok(path),
+      Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+      Err(e) => return Err(e.into())
+    }
+  }
+  return Err(JumpcutError::Io(io::Error::new(io::ErrorKind::AlreadyExists, "could not create a temporary file")));
+}
+
+/// Returns the user's preferred editor (and its arguments, if any)
+fn editor_command() -> Vec<String> {
+  let editor = env::var("VISUAL").ok().filter(|x| !x.trim().is_empty())
+    .or_else(|| env::var("EDITOR").ok().filter(|x| !x.trim().is_empty()))
+    .unwrap_or_else(|| (if cfg!(windows) {"notepad"} else {"vi"}).to_string());
+  return editor.split_whitespace().map(|x| x.to_string()).collect();
+}
+
+/// Opens `path` in the editor, and waits until it is closed
+fn run_editor(editor: &[String], path: &Path) -> Result<(), JumpcutError> {
+  let status = Command::new(&editor[0]).args(&editor[1..]).arg(path).status()
+    .map_err(|e| JumpcutError::InvalidArgument(format!("could not start editor {:?}: {}", editor.join(" "), e)))?;
+  if !status.success() {
+    // (Most editors only fail if the user aborted)
+    fs::remove_file(path).ok();
+    return Err(JumpcutError::Cancelled);
+  }
+  return Ok(());
+}
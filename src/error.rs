@@ -0,0 +1,93 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Everything that can go wrong in Jumpcut
+///
+/// Each kind of error has its own exit code (see `exit_code`), so scripts and the shell wrapper can tell them apart.
+#[derive(Debug)]
+pub enum JumpcutError {
+  /// Invalid command-line arguments or configuration
+  InvalidArgument(String),
+  /// No alias matches the given name/query
+  NotFound(String),
+  /// The given query matches several aliases (query, names of all matches)
+  Ambiguous(String, Vec<String>),
+  /// The given name is a reserved keyword, so it can't be used as an alias name
+  Reserved(String),
+  /// An alias with the given name already exists
+  AlreadyExists(String),
+  /// A file (e.g. an alias file) could not be parsed (path, reason)
+  InvalidFile(PathBuf, String),
+  /// The user needs to provide input (e.g. a parameter value or a confirmation), but prompts are disabled
+  InputRequired(String),
+  /// The alias store with the given name is read-only, so its aliases can't be changed
+  ReadOnly(String),
+  /// Synchronizing an alias store with its git remote failed
+  Sync(String),
+  /// The user cancelled a prompt or did not confirm an action
+  Cancelled,
+  /// Reading or writing a file failed
+  Io(io::Error)
+}
+
+impl JumpcutError {
+  /// Returns the exit code Jumpcut uses for this error
+  ///
+  /// | Code | Error             |
+  /// |------|-------------------|
+  /// | 1    | `InvalidArgument` |
+  /// | 2    | `NotFound`        |
+  /// | 3    | `Ambiguous`       |
+  /// | 4    | `Reserved`        |
+  /// | 5    | `AlreadyExists`   |
+  /// | 6    | `InvalidFile`     |
+  /// | 7    | `Io`              |
+  /// | 8    | `InputRequired`   |
+  /// | 9    | `ReadOnly`        |
+  /// | 11   | `Sync`            |
+  /// | 130  | `Cancelled`       |
+  ///
+  /// (Exit code 0 means success, and 10 is reserved for handing over a command to the wrapper; see `init::EXIT_EVAL`.)
+  pub fn exit_code(&self) -> i32 {
+    return match self {
+      JumpcutError::InvalidArgument(_) => 1,
+      JumpcutError::NotFound(_) => 2,
+      JumpcutError::Ambiguous(_, _) => 3,
+      JumpcutError::Reserved(_) => 4,
+      JumpcutError::AlreadyExists(_) => 5,
+      JumpcutError::InvalidFile(_, _) => 6,
+      JumpcutError::Io(_) => 7,
+      JumpcutError::InputRequired(_) => 8,
+      JumpcutError::ReadOnly(_) => 9,
+      JumpcutError::Sync(_) => 11,
+      JumpcutError::Cancelled => 130
+    };
+  }
+}
+
+impl fmt::Display for JumpcutError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    return match self {
+      JumpcutError::InvalidArgument(msg) => write!(f, "{}", msg),
+      JumpcutError::NotFound(query) => write!(f, "no alias found matching {:?}.", query),
+      JumpcutError::Ambiguous(query, matches) => write!(f, "{:?} matches multiple aliases ({}); please be more specific.", query, matches.join(", ")),
+      JumpcutError::Reserved(alias) => write!(f, "{:?} cannot be used as an alias name; it is a reserved keyword.", alias),
+      JumpcutError::AlreadyExists(alias) => write!(f, "alias {:?} already exists.", alias),
+      JumpcutError::InvalidFile(path, reason) => write!(f, "invalid file {}: {}", path.display(), reason),
+      JumpcutError::InputRequired(msg) => write!(f, "{}", msg),
+      JumpcutError::ReadOnly(store) => write!(f, "the {:?} alias store is read-only.", store),
+      JumpcutError::Sync(msg) => write!(f, "sync failed: {}", msg),
+      JumpcutError::Cancelled => write!(f, "cancelled."),
+      JumpcutError::Io(e) => write!(f, "{}", e)
+    };
+  }
+}
+
+impl std::error::Error for JumpcutError {}
+
+impl From<io::Error> for JumpcutError {
+  fn from(e: io::Error) -> JumpcutError {
+    return JumpcutError::Io(e);
+  }
+}
@@ -0,0 +1,240 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use crate::error::JumpcutError;
+use crate::shell::Shell;
+
+/// Location of the shell's history file; set by the wrapper function for bash and zsh, as they don't export HISTFILE
+pub const JUMPCUT_HISTFILE_ENV_VAR: &str = "JUMPCUT_HISTFILE";
+
+/// A word in a command, and where it occurs in the command
+#[derive(Clone, PartialEq, Debug)]
+pub struct Word {
+  pub text: String, // the word without quotes/escapes
+  pub start: usize, // byte offsets, including any quotes
+  pub end: usize
+}
+
+/// Returns the location of `shell`'s history file, or None if reading its history is not supported
+pub fn history_file(shell: Shell) -> Option<PathBuf> {
+  let from_env = |name: &str| env::var_os(name).filter(|x| !x.is_empty()).map(PathBuf::from);
+  if let Some(path) = from_env(JUMPCUT_HISTFILE_ENV_VAR) {
+    return Some(path);
+  }
+  let home = dirs::home_dir()?;
+  let data_dir = from_env("XDG_DATA_HOME").unwrap_or_else(|| home.join(".local").join("share"));
+  return match shell {
+    Shell::Posix | Shell::Bash => Some(from_env("HISTFILE").unwrap_or_else(|| home.join(".bash_history"))),
+    Shell::Zsh => Some(from_env("HISTFILE").unwrap_or_else(|| from_env("ZDOTDIR").unwrap_or(home).join(".zsh_history"))),
+    Shell::Fish => {
+      // (The name of the history file depends on the fish_history variable)
+      let session = env::var("fish_history").ok().filter(|x| !x.is_empty()).unwrap_or_else(|| "fish".to_string());
+      Some(data_dir.join("fish").join(format!("{}_history", session)))
+    },
+    Shell::PowerShell => {
+      let dir = if cfg!(windows) {dirs::data_dir()?.join("Microsoft").join("Windows").join("PowerShell")} else {data_dir.join("powershell")};
+      Some(dir.join("PSReadLine").join("ConsoleHost_history.txt"))
+    },
+    Shell::Nushell => None
+  };
+}
+
+/// Reads the commands in a history file of `shell`, from oldest to newest
+pub fn read_history(shell: Shell, path: &Path) -> Result<Vec<String>, JumpcutError> {
+  let data = fs::read(path)?;
+  let contents = if shell == Shell::Zsh {unmetafy(&data)} else {String::from_utf8_lossy(&data).to_string()};
+  return Ok(parse_history(shell, &contents));
+}
+
+/// Parses the contents of a history file (see `read_history`)
+///
+/// Supports bash (with or without timestamps), zsh (with or without extended history), fish and PowerShell (PSReadLine).
+pub fn parse_history(shell: Shell, contents: &str) -> Vec<String> {
+  let mut commands = Vec::new();
+  if shell == Shell::Fish {
+    // (fish uses a YAML-like format, with a `- cmd: ..` line for each command)
+    for line in contents.lines() {
+      if let Some(cmd) = line.strip_prefix("- cmd: ") {
+        commands.push(unescape_fish(cmd));
+      }
+    }
+    return commands;
+  }
+
+  let bash_timestamp = Regex::new(r"^#\d+$").unwrap();
+  let zsh_extended = Regex::new(r"^: *\d+:\d+;").unwrap();
+  // (zsh and PSReadLine end each line of a multi-line command with a backslash/backtick)
+  let continuation = match shell {
+    Shell::Zsh => Some('\\'),
+    Shell::PowerShell => Some('`'),
+    _ => None
+  };
+  let mut pending: Option<String> = None;
+  for line in contents.lines() {
+    let line = match pending.take() {
+      Some(previous) => format!("{}\n{}", previous, line),
+      None if shell == Shell::Zsh => zsh_extended.replace(line, "").to_string(),
+      None if bash_timestamp.is_match(line) => continue,
+      None => line.to_string()
+    };
+    match continuation {
+      Some(c) if line.ends_with(c) => pending = Some(line[..line.len() - c.len_utf8()].to_string()),
+      _ => commands.push(line)
+    }
+  }
+  commands.extend(pending);
+  return commands;
+}
+
+/// Returns the last `count` commands (from oldest to newest), skipping empty commands and repeated commands
+///
+/// Invocations of `addlast` itself are skipped as well, as they end up in the history before Jumpcut reads it. These are
+/// recognized by their first word, which is one of `jumpcut_names` (e.g. the name of the wrapper function or binary).
+pub fn recent_commands(commands: &[String], count: usize, jumpcut_names: &[String]) -> Vec<String> {
+  let mut recent: Vec<String> = Vec::new();
+  for cmd in commands.iter().rev() {
+    if recent.len() >= count {
+      break;
+    }
+    let mut words = cmd.split_whitespace();
+    let is_addlast = match (words.next(), words.next()) {
+      // (The binary may be called by its path, e.g. `~/.cargo/bin/jumpcut`)
+      (Some(program), Some("addlast")) => Path::new(program).file_stem().and_then(|name| name.to_str())
+        .map(|name| jumpcut_names.iter().any(|x| x == name)) == Some(true),
+      _ => false
+    };
+    if cmd.trim().is_empty() || is_addlast || recent.contains(cmd) {
+      continue;
+    }
+    recent.push(cmd.clone());
+  }
+  recent.reverse();
+  return recent;
+}
+
+/// zsh escapes some bytes in its history file by preceding them with 0x83, and XOR-ing them with 32
+fn unmetafy(data: &[u8]) -> String {
+  let mut bytes = Vec::with_capacity(data.len());
+  let mut iter = data.iter();
+  while let Some(byte) = iter.next() {
+    match (*byte, iter.as_slice().first()) {
+      (0x83, Some(next)) => {
+        bytes.push(next ^ 32);
+        iter.next();
+      },
+      (byte, _) => bytes.push(byte)
+    }
+  }
+  return String::from_utf8_lossy(&bytes).to_string();
+}
+
+/// fish escapes backslashes and newlines in its history file
+fn unescape_fish(cmd: &str) -> String {
+  let mut out = String::new();
+  let mut chars = cmd.chars();
+  while let Some(c) = chars.next() {
+    match (c, chars.clone().next()) {
+      ('\\', Some('n')) => {
+        out.push('\n');
+        chars.next();
+      },
+      ('\\', Some('\\')) => {
+        out.push('\\');
+        chars.next();
+      },
+      (c, _) => out.push(c)
+    }
+  }
+  return out;
+}
+
+/// Returns the literal arguments of a command, i.e. the words that could be turned into parameters
+///
+/// This leaves out the commands themselves (e.g. `git` in `git push | tee log`), options (e.g. `-v`), variable
+/// assignments, and words whose value is only known when the command runs (e.g. `$HOME` or `*.txt`).
+pub fn literal_arguments(command: &str) -> Vec<Word> {
+  let mut arguments = Vec::new();
+  let mut is_command_position = true;
+  let mut word: Option<(Word, bool)> = None; // (the word so far, whether its value is dynamic)
+  // (A space is added at the end, to finish the last word)
+  let mut chars = command.char_indices().chain(std::iter::once((command.len(), ' ')));
+  let mut quote = None;
+  let mut previous = ' ';
+
+  while let Some((i, c)) = chars.next() {
+    let (current, is_dynamic) = word.get_or_insert_with(|| (Word{text: String::new(), start: i, end: i}, false));
+    match (quote, c) {
+      (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+      (Some('"'), '\\') => current.text.extend(chars.next().map(|(_, c)| c)),
+      (Some('"'), '$') | (Some('"'), '`') => {
+        *is_dynamic = true;
+        current.text.push(c);
+      },
+      (Some(_), c) => current.text.push(c),
+      (None, '\'') | (None, '"') => quote = Some(c),
+      (None, '\\') => current.text.extend(chars.next().map(|(_, c)| c)),
+      (None, c) if c.is_whitespace() || ";|&()<>".contains(c) => {
+        let (mut finished, is_dynamic) = word.take().unwrap();
+        if finished.start < i {
+          finished.end = i;
+          let is_assignment = is_command_position && finished.text.contains('=');
+          // (e.g. the 2 and 1 in `2>&1`)
+          let is_file_descriptor = ("<>".contains(c) && finished.text.chars().all(|x| x.is_ascii_digit()))
+            || command[..finished.start].ends_with(">&") || command[..finished.start].ends_with("<&");
+          if !is_command_position && !is_dynamic && !is_file_descriptor && !finished.text.starts_with('-') {
+            arguments.push(finished);
+          }
+          is_command_position = is_assignment;
+        }
+        if ";|(".contains(c) || (c == '&' && !"<>".contains(previous)) {
+          is_command_position = true;
+        }
+      },
+      (None, c) => {
+        if "$`*?[{~".contains(c) {
+          *is_dynamic = true;
+        }
+        current.text.push(c);
+      }
+    }
+    previous = c;
+  }
+  return arguments;
+}
+
+/// Replaces words of a command by parameter placeholders (word, name of the parameter)
+pub fn parameterize(command: &str, replacements: &[(Word, String)]) -> String {
+  let mut replacements = replacements.to_vec();
+  replacements.sort_by_key(|(word, _)| word.start);
+  let mut out = String::new();
+  let mut pos = 0;
+  for (word, name) in replacements.iter() {
+    if word.start < pos {
+      continue;
+    }
+    out.push_str(&command[pos..word.start]);
+    out.push_str(&format!("?[{}]", name));
+    pos = word.end;
+  }
+  out.push_str(&command[pos..]);
+  return out;
+}
+
+/// Proposes a name for a parameter, based on an example value (e.g. "n" for a number), avoiding the `taken` names
+pub fn suggest_param_name(value: &str, taken: &[String]) -> String {
+  let base = if value.parse::<f64>().is_ok() {
+    "n"
+  } else if value.contains("://") {
+    "url"
+  } else if Path::new(value).is_dir() {
+    "dir"
+  } else if Path::new(value).is_file() || value.contains('/') || value.contains('.') {
+    "file"
+  } else {
+    "arg"
+  };
+  return (1..).map(|i| if i == 1 {base.to_string()} else {format!("{}{}", base, i)})
+    .find(|name| !taken.contains(name))
+    .unwrap_or_default();
+}
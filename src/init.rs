@@ -0,0 +1,193 @@
+use regex::Regex;
+use crate::shell::Shell;
+
+/// Environment variable through which the shell wrapper tells us where to write the command it should evaluate
+pub const JUMPCUT_CMD_FILE_ENV_VAR: &str = "JUMPCUT_CMD_FILE";
+
+/// Environment variable through which the shell wrapper tells us its own name (e.g. "j")
+pub const JUMPCUT_NAME_ENV_VAR: &str = "JUMPCUT_NAME";
+
+/// Exit code that tells the wrapper to evaluate the command in JUMPCUT_CMD_FILE
+/// 
+/// (Exit code 0 means there is nothing left to do; any other exit code means something went wrong.)
+pub const EXIT_EVAL: i32 = 10;
+
+// Protocol between the wrapper and Jumpcut: for each call, the wrapper creates a private temporary folder, and passes
+// the path of a (not yet existing) file in that folder in JUMPCUT_CMD_FILE. If Jumpcut exits with EXIT_EVAL, the
+// wrapper evaluates (or sources) the command that was written to that file. The folder is removed afterwards.
+// All other output (prompts, lists, errors) goes straight to the terminal.
+
+// bash and zsh only write their history file when they exit, so it's written first for `addlast`
+const POSIX_WRAPPER: &str = r#"__NAME__() {
+  local jumpcut_dir jumpcut_status
+  jumpcut_dir=$(command mktemp -d "${TMPDIR:-/tmp}/jumpcut.XXXXXX") || return
+  if [ "$1" = addlast ]; then
+    [ -n "$BASH_VERSION" ] && history -a
+    [ -n "$ZSH_VERSION" ] && [ -n "$HISTFILE" ] && fc -AI
+  fi
+  JUMPCUT_SHELL=__SHELL__ JUMPCUT_NAME=__NAME__ JUMPCUT_CMD_FILE="$jumpcut_dir/cmd" JUMPCUT_HISTFILE="$HISTFILE" __BIN__ "$@"
+  jumpcut_status=$?
+  if [ $jumpcut_status -eq __EXIT_EVAL__ ]; then
+    . "$jumpcut_dir/cmd"
+    jumpcut_status=$?
+  fi
+  command rm -rf "$jumpcut_dir"
+  return $jumpcut_status
+}
+"#;
+
+const FISH_WRAPPER: &str = r#"function __NAME__
+  set -l jumpcut_tmp /tmp
+  set -q TMPDIR; and set jumpcut_tmp $TMPDIR
+  set -l jumpcut_dir (command mktemp -d $jumpcut_tmp/jumpcut.XXXXXX); or return
+  set -lx JUMPCUT_SHELL fish
+  set -lx JUMPCUT_NAME __NAME__
+  set -lx JUMPCUT_CMD_FILE $jumpcut_dir/cmd
+  __BIN__ $argv
+  set -l jumpcut_status $status
+  if test $jumpcut_status -eq __EXIT_EVAL__
+    set -l jumpcut_cmd (string collect < $JUMPCUT_CMD_FILE)
+    command rm -rf $jumpcut_dir
+    eval $jumpcut_cmd
+  else
+    command rm -rf $jumpcut_dir
+    return $jumpcut_status
+  end
+end
+"#;
+
+// The environment variables are restored afterwards, so they don't leak into other processes started from the session
+const POWERSHELL_WRAPPER: &str = r#"function __NAME__ {
+  $jumpcut_dir = Join-Path ([System.IO.Path]::GetTempPath()) ("jumpcut." + [System.IO.Path]::GetRandomFileName())
+  New-Item -ItemType Directory -Path $jumpcut_dir -ErrorAction Stop | Out-Null
+  $jumpcut_saved = @{}
+  foreach ($jumpcut_var in 'JUMPCUT_SHELL', 'JUMPCUT_NAME', 'JUMPCUT_CMD_FILE') {
+    $jumpcut_saved[$jumpcut_var] = [Environment]::GetEnvironmentVariable($jumpcut_var)
+  }
+  try {
+    $env:JUMPCUT_SHELL = 'powershell'
+    $env:JUMPCUT_NAME = '__NAME__'
+    $env:JUMPCUT_CMD_FILE = Join-Path $jumpcut_dir 'cmd'
+    & __BIN__ @args
+    $jumpcut_status = $LASTEXITCODE
+    $jumpcut_cmd = if ($jumpcut_status -eq __EXIT_EVAL__) { Get-Content -Raw $env:JUMPCUT_CMD_FILE }
+  } finally {
+    foreach ($jumpcut_var in $jumpcut_saved.Keys) {
+      [Environment]::SetEnvironmentVariable($jumpcut_var, $jumpcut_saved[$jumpcut_var])
+    }
+    Remove-Item -Recurse -Force $jumpcut_dir
+  }
+  if ($jumpcut_status -eq __EXIT_EVAL__) {
+    Invoke-Expression $jumpcut_cmd
+  } else {
+    $global:LASTEXITCODE = $jumpcut_status
+  }
+}
+"#;
+// Nushell cannot evaluate a string in the current scope, so `cd` commands are handled by the wrapper itself,
+// and everything else runs in a nested nushell
+const NUSHELL_WRAPPER: &str = r#"def --env --wrapped __NAME__ [...args: string@"nu-complete jumpcut __NAME__"] {
+  let jumpcut_dir = (mktemp --directory --tmpdir jumpcut.XXXXXX)
+  let jumpcut_cmd_file = ($jumpcut_dir | path join cmd)
+  $env.JUMPCUT_SHELL = 'nushell'
+  $env.JUMPCUT_NAME = '__NAME__'
+  $env.JUMPCUT_CMD_FILE = $jumpcut_cmd_file
+  try { ^__BIN__ ...$args }
+  let jumpcut_status = $env.LAST_EXIT_CODE
+  hide-env JUMPCUT_CMD_FILE
+  let jumpcut_cmd = if $jumpcut_status == __EXIT_EVAL__ { open --raw $jumpcut_cmd_file | str trim } else { "" }
+  rm --recursive --permanent $jumpcut_dir
+  if $jumpcut_status == __EXIT_EVAL__ {
+    let dir = ($jumpcut_cmd | parse --regex '^cd "(?P<dir>[^"]*)"$')
+    if ($dir | is-not-empty) {
+      cd ($dir | get dir.0)
+    } else {
+      nu -c $jumpcut_cmd
+    }
+  }
+}
+"#;
+
+// Completion scripts pass the words typed so far to `jumpcut __complete`, which prints one candidate per line.
+// Alias names are matched anywhere in the name (just like when executing an alias), so shells should not filter
+// the candidates by prefix.
+
+const BASH_COMPLETION: &str = r#"_jumpcut_complete___NAME__() {
+  local IFS=$'\n'
+  COMPREPLY=($(__BIN__ __complete "${COMP_WORDS[@]:1:$COMP_CWORD}"))
+  [[ "${COMPREPLY[0]}" == *= ]] && compopt -o nospace 2> /dev/null
+}
+complete -F _jumpcut_complete___NAME__ __NAME__
+"#;
+
+const ZSH_COMPLETION: &str = r#"_jumpcut_complete___NAME__() {
+  local -a candidates
+  candidates=("${(@f)$(__BIN__ __complete "${(@)words[2,$CURRENT]}")}")
+  compadd -U -S '' -- "${(@)candidates:#*=}"
+  compadd -U -- "${(@M)candidates:#*=}"
+}
+(( $+functions[compdef] )) && compdef _jumpcut_complete___NAME__ __NAME__
+"#;
+
+const FISH_COMPLETION: &str = r#"complete -c __NAME__ -f -a '(__BIN__ __complete (commandline -opc)[2..-1] (commandline -ct))'
+"#;
+
+const POWERSHELL_COMPLETION: &str = r#"Register-ArgumentCompleter -Native -CommandName __NAME__ -ScriptBlock {
+  param($wordToComplete, $commandAst, $cursorPosition)
+  $words = @($commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object { $_.ToString() })
+  if ($wordToComplete -eq '') { $words += '""' }
+  & __BIN__ __complete @words | ForEach-Object {
+    [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)
+  }
+}
+"#;
+
+const NUSHELL_COMPLETION: &str = r#"def "nu-complete jumpcut __NAME__" [context: string] {
+  ^__BIN__ __complete ...($context | split row -r '\s+' | skip 1) | lines
+}
+"#;
+
+/// Returns the wrapper function that needs to be loaded in `shell` to use Jumpcut
+///
+/// `name` : name of the wrapper function (i.e. the command users type to invoke Jumpcut)
+/// `bin`  : absolute path to the Jumpcut binary
+pub fn wrapper_script(shell: Shell, name: &str, bin: &str) -> String {
+  let template = match shell {
+    Shell::Posix | Shell::Bash | Shell::Zsh => POSIX_WRAPPER,
+    Shell::Fish => FISH_WRAPPER,
+    Shell::PowerShell => POWERSHELL_WRAPPER,
+    Shell::Nushell => NUSHELL_WRAPPER
+  };
+  return fill_in_template(template, shell, name, bin);
+}
+
+/// Returns the script that enables tab-completion for the wrapper function in `shell`
+///
+/// See `wrapper_script` for the meaning of the arguments.
+pub fn completion_script(shell: Shell, name: &str, bin: &str) -> String {
+  let template = match shell {
+    // (Plain POSIX shells don't support completion)
+    Shell::Posix => "",
+    Shell::Bash => BASH_COMPLETION,
+    Shell::Zsh => ZSH_COMPLETION,
+    Shell::Fish => FISH_COMPLETION,
+    Shell::PowerShell => POWERSHELL_COMPLETION,
+    Shell::Nushell => NUSHELL_COMPLETION
+  };
+  return fill_in_template(template, shell, name, bin);
+}
+
+/// Fills in the placeholders of a wrapper/completion script template
+fn fill_in_template(template: &str, shell: Shell, name: &str, bin: &str) -> String {
+  return template
+    .replace("__NAME__", name)
+    .replace("__SHELL__", shell.name())
+    .replace("__EXIT_EVAL__", &EXIT_EVAL.to_string())
+    .replace("__BIN__", &shell.quote(bin));
+}
+
+/// Can `name` be used as the name of the wrapper function?
+pub fn is_valid_function_name(name: &str) -> bool {
+  let re = Regex::new(r"^[A-Za-z_][A-Za-z0-9_-]*$").unwrap();
+  return re.is_match(name);
+}
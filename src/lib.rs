@@ -0,0 +1,40 @@
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
+
+//! Jumpcut - a command-line utility to quickly access frequently-used commands/folders
+//!
+//! Besides the `jumpcut` binary, this library gives other tools (e.g. editor plugins) access to
+//! Jumpcut's aliases and alias matching, without having to call the binary:
+//! - `store::AliasStore` lists, reads and modifies aliases (`store::default_stores` returns the stores Jumpcut uses,
+//!   as configured in `config::Config`)
+//! - `matcher::Matcher` fuzzily matches alias names against a query
+//! - `resolve` determines which alias a query refers to, without printing or prompting anything
+
+extern crate dirs;
+extern crate regex;
+
+pub mod alias;
+pub mod browse;
+pub mod bundle;
+pub mod config;
+pub mod dir_import;
+pub mod error;
+pub mod history;
+pub mod init;
+pub mod matcher;
+pub mod param;
+pub mod paths;
+pub mod resolve;
+pub mod shell;
+pub mod shell_import;
+pub mod stats;
+pub mod store;
+pub mod suggest;
+pub mod sync;
+pub mod trust;
+pub mod utils;
+
+pub use alias::Alias;
+pub use error::JumpcutError;
+pub use matcher::Matcher;
+pub use resolve::{rank, resolve, Candidate, Resolution, ResolveOptions};
+pub use store::{AliasStore, FsStore};
@@ -1,3 +1,5 @@
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
+
 extern crate dialoguer;
 extern crate dirs;
 extern crate regex;
@@ -5,22 +7,61 @@ extern crate regex;
 use std::collections::HashMap;
 use std::env;
 use std::io;
+use std::io::Read;
+use std::process;
 use std::fs;
-use std::path::{Path, PathBuf};
-use dialoguer::{Confirm, Select, Input};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::PathBuf;
+use dialoguer::{Confirm, Input, MultiSelect, Select};
+use dialoguer::console::style;
 use regex::Regex;
 
-mod utils;
-use utils::*;
-pub mod alias;
-use alias::Alias;
+use jumpcut::paths::*;
+use jumpcut::utils::*;
+use jumpcut::error::JumpcutError;
+use jumpcut::alias::{Alias, is_legacy_format};
+use jumpcut::bundle::{Bundle, ImportAction, OnConflict};
+use jumpcut::param::Param;
+use jumpcut::shell::Shell;
+use jumpcut::shell_import::parse_shell_config;
+use jumpcut::init;
+use jumpcut::stats;
+use jumpcut::stats::UsageStats;
+use jumpcut::sync::{sync, Change, ChangeKind, ConflictChoice};
+use jumpcut::trust::{diff_lines, DiffLine, TrustDb, TrustStatus};
+use jumpcut::history::{history_file, literal_arguments, parameterize, read_history, recent_commands, suggest_param_name};
+use jumpcut::suggest::suggest;
+use jumpcut::dir_import::{cd_command, propose_names, DirTool};
+use jumpcut::config::{Config, ConflictRule};
+use jumpcut::store::{AliasStore, FsStore, PERSONAL_STORE, PROJECT_FOLDER, PROJECT_STORE, default_stores, personal_store, project_store, shared_store};
+use jumpcut::resolve::{resolve, Resolution, ResolveOptions};
+mod cli;
+use cli::*;
+mod edit;
+mod protocol;
+use protocol::{emit_command, emit_nothing};
+mod ui;
 
 
 /// Jumpcut - a command-line utility to quickly access frequently-used commands/folders
 /// 
 /// Run without any parameters to display the usage message.
-fn main() -> Result<(),io::Error> {
-    let args: Vec<String> = env::args().collect();
+/// If anything goes wrong, an error message is printed and Jumpcut exits with the error's exit code (see `JumpcutError::exit_code`).
+fn main() {
+    if let Err(e) = run(env::args().collect()) {
+        if !matches!(e, JumpcutError::Cancelled) {
+            error(&e.to_string());
+        }
+        process::exit(e.exit_code());
+    }
+}
+
+/// Perform the action described by the command-line arguments
+fn run(mut args: Vec<String>) -> Result<(), JumpcutError> {
+    while args.len() > 1 && args[1] == "--no-input" {
+        args.remove(1);
+        disable_input();
+    }
     if args.len() == 1 {
         usage();
         return Ok(());
@@ -28,7 +69,7 @@ fn main() -> Result<(),io::Error> {
 
     fs::create_dir_all(alias_path())?;
 
-    let action = &args[1];
+    let action = args[1].clone();
     match action.as_ref() {
         "is_exec_action" => {
             if args.len()>2 {
@@ -43,71 +84,172 @@ fn main() -> Result<(),io::Error> {
         }
 
         "add" => {
-            if args_ok(&args, 2) {
-                let cmd = args[3..].join(" ");
-                return add_alias(&args[2], &cmd, &alias_path());
+            let project = args.len() > 2 && args[2] == "--project";
+            if project {
+                args.remove(2);
             }
+            args_ok(&args, 2)?;
+            let cmd = command_from_args(&args[3..])?;
+            let store = if project {project_store_or_new()?} else {personal_store()};
+            return add_alias(&args[2], &cmd, &store);
         }
 
         "addwd" => {
-            if args_ok(&args, 2) {
-                let abs_pwd = absolute_path(&env::current_dir().unwrap());
-                let cmd = args[3..].join(" ");
-                return add_alias(&args[2], &format!("cd \"{}\";{};cd ?pwd", abs_pwd, cmd), &alias_path());
-            }
+            args_ok(&args, 2)?;
+            let abs_pwd = absolute_path(&env::current_dir()?)?;
+            let cmd = command_from_args(&args[3..])?;
+            return add_alias(&args[2], &format!("cd \"{}\";{};cd ?pwd", abs_pwd, cmd), &personal_store());
         }
 
         "addpath" => {
-            if args_ok(&args, 1) {
-                let path = if args.len() > 3 {args[3..].join(" ")} else {".".to_string()};
-                let abs_path = absolute_path(&PathBuf::from(path));
-                return add_alias(&args[2], &format!("cd \"{}\"", abs_path), &alias_path());
-            }
+            args_ok(&args, 1)?;
+            let path = if args.len() > 3 {args[3..].join(" ")} else {".".to_string()};
+            let abs_path = absolute_path(&PathBuf::from(path))?;
+            return add_alias(&args[2], &format!("cd \"{}\"", abs_path), &personal_store());
+        }
+
+        "addlast" => {
+            args_ok(&args, 1)?;
+            let count = parse_option::<usize>(args.get(3).cloned(), "N")?;
+            return add_last_command(&args[2], count);
+        }
+
+        "suggest" => {
+            let min_count = parse_option::<usize>(take_option(&mut args, "--min-count")?, "--min-count")?;
+            let top = parse_option::<usize>(take_option(&mut args, "--top")?, "--top")?;
+            return suggest_aliases(min_count.unwrap_or(3), top.unwrap_or(10));
         }
 
         "addshr" => {
-            if args_ok(&args, 2) {
-                let cmd = args[3..].join(" ");
-                return match alias_shared_path() {
-                    Some(shared_path) => add_alias(&args[2], &cmd, &shared_path),
-                    None => {
-                        return error(&format!("no shared storage path configured! Please set the {} environment variable.", JUMPCUT_SHARED_ENV_VAR));
-                    }
-                };
-            }
+            args_ok(&args, 2)?;
+            let cmd = command_from_args(&args[3..])?;
+            return add_alias(&args[2], &cmd, &require_shared_store()?);
         }
 
         "desc" => {
-            if args_ok(&args, 2) {
-                let desc = args[3..].join(" ");
-                return add_description(&args[2], &desc);
-            }
+            args_ok(&args, 2)?;
+            let desc = args[3..].join(" ");
+            return add_description(&args[2], &desc);
+        }
+
+        "edit" => {
+            args_ok(&args, 1)?;
+            return edit::edit_alias(&args[2]);
         }
 
         "confirm" => {
-            if args_ok(&args, 2) {
-                return set_confirmation(&args[2], args[3].parse::<i8>().unwrap_or_default());
-            }
+            args_ok(&args, 2)?;
+            let level = args[3].parse::<i8>().ok().filter(|level| (0..=2).contains(level)).ok_or_else(||
+                JumpcutError::InvalidArgument(format!("the confirmation level must be 0, 1 or 2 (not {:?}).", args[3])))?;
+            return set_confirmation(&args[2], level);
         }
 
         "cp" => {
-            if args_ok(&args, 2) {
-                copy_alias(&args[2], &args[3], alias_path())?;
-            }
+            args_ok(&args, 2)?;
+            return copy_alias(&args[2], &args[3], &personal_store());
         },
 
         "cpshr" => {
-            if args_ok(&args, 2) {
-                match alias_shared_path() {
-                    Some(x) => copy_alias(&args[2], &args[3], x)?,
-                    None => error(&format!("no shared storage path configured! Please set the {} environment variable.", JUMPCUT_SHARED_ENV_VAR))?
-                }
-            }
+            args_ok(&args, 2)?;
+            return copy_alias(&args[2], &args[3], &require_shared_store()?);
+        },
+
+        "mv" => {
+            args_ok(&args, 2)?;
+            let (store, _) = load_alias(&args[2])?;
+            return move_alias(&args[2], &store, &args[3], &store);
+        },
+
+        "promote" => {
+            args_ok(&args, 1)?;
+            return move_alias(&args[2], &personal_store(), &args[2], &require_shared_store()?);
+        },
+
+        "demote" => {
+            args_ok(&args, 1)?;
+            return move_alias(&args[2], &require_shared_store()?, &args[2], &personal_store());
         },
 
         "rm" => {
-            if args_ok(&args, 1) {
-                remove_alias(&args[2])?;
+            args_ok(&args, 1)?;
+            return remove_alias(&args[2]);
+        },
+
+        "trust" => {
+            args_ok(&args, 1)?;
+            return trust_alias(&args[2]);
+        },
+
+        "sync" => {
+            return sync_store(args.get(2).map(|x| x.as_str()));
+        },
+
+        "export" => {
+            let store = take_option(&mut args, "--store")?;
+            return export_aliases(args[2..].to_vec(), store.as_deref());
+        },
+
+        "import" => {
+            let store = take_option(&mut args, "--store")?;
+            let prefix = take_option(&mut args, "--prefix")?.unwrap_or_default();
+            let on_conflict = match take_option(&mut args, "--on-conflict")? {
+                Some(name) => OnConflict::from_name(&name).ok_or_else(|| JumpcutError::InvalidArgument(
+                    format!("unknown conflict strategy {:?}; choose one of skip, overwrite, rename or ask.", name)))?,
+                None => OnConflict::Skip
+            };
+            let dry_run = take_flag(&mut args, "--dry-run");
+            args_ok(&args, 1)?;
+            return import_aliases(&args[2], store.as_deref(), &prefix, on_conflict, dry_run);
+        },
+
+        "import-shell" => {
+            let store = take_option(&mut args, "--store")?;
+            args_ok(&args, 2)?;
+            return import_shell_aliases(&args[2], &args[3], store.as_deref());
+        },
+
+        "import-dirs" => {
+            let top = parse_option::<usize>(take_option(&mut args, "--top")?, "--top")?;
+            let min_score = parse_option::<f64>(take_option(&mut args, "--min-score")?, "--min-score")?;
+            let file = take_option(&mut args, "--file")?;
+            args_ok(&args, 1)?;
+            return import_dirs(&args[2], file.map(PathBuf::from), top, min_score);
+        },
+
+        "migrate" => {
+            return migrate_stores();
+        },
+
+        "stats" => {
+            return show_stats();
+        },
+
+        "ui" => {
+            if !is_interactive() {
+                return Err(JumpcutError::InputRequired("`j ui` can only be used in an interactive terminal.".to_string()));
+            }
+            let stores = default_stores()?;
+            return match ui::browse(&stores)? {
+                Some((store, name)) => emit(Some(exec_alias(&name, HashMap::new(), &stores[store])?)),
+                None => emit(None)
+            };
+        },
+
+        "init" => {
+            args_ok(&args, 1)?;
+            let (shell, name, bin) = parse_shell_args(&args[2..])?;
+            print!("{}{}", init::completion_script(shell, &name, &bin), init::wrapper_script(shell, &name, &bin));
+        },
+
+        "completions" => {
+            args_ok(&args, 1)?;
+            let (shell, name, bin) = parse_shell_args(&args[2..])?;
+            print!("{}", init::completion_script(shell, &name, &bin));
+        },
+
+        "__complete" => {
+            for candidate in complete_words(&args[2..]) {
+                println!("{}", candidate);
             }
         },
 
@@ -115,85 +257,171 @@ fn main() -> Result<(),io::Error> {
             let arg_regex = Regex::new(r"--([A-Za-z0-9_]+)=(.+)").unwrap();
             let mut alias_args = HashMap::new();
             let mut alias_name_parts = Vec::new();
-            for arg in args[1..].iter() {
+            let mut args_iter = args[1..].iter();
+            for arg in args_iter.by_ref() {
+                // Everything after the separator is a positional argument
+                if arg == "--" || arg == "---" {
+                    break;
+                }
                 match arg_regex.captures(arg) {
-                    Some(x) => {alias_args.insert(x[1].to_string(), x[2].to_string()); ()},
+                    Some(x) => {alias_args.insert(x[1].to_string(), x[2].to_string());},
                     None    => alias_name_parts.push(arg.clone())
                 }
             }
-            find_and_exec_alias(alias_name_parts, alias_args).ok();
+            for (i, arg) in args_iter.enumerate() {
+                alias_args.insert((i + 1).to_string(), arg.clone());
+            }
+            return emit(Some(find_and_exec_alias(alias_name_parts, alias_args)?));
         }
     };
 
     return Ok(());
 }
 
-/// Is `action` a reserved keyword or is it an alias name?
-fn is_reserved_keyword(action: &str) -> bool {
-    let reserved_keywords = [
-        "is_exec_action", "list",
-        "add", "addwd", "addpath", "addshr",
-        "desc", "confirm", "rm", "cp", "cpshr"];
-    return reserved_keywords.contains(&action);
+/// Hand over the command to be executed (if any) to the shell wrapper
+fn emit(cmd: Option<String>) -> Result<(), JumpcutError> {
+    return match cmd {
+        Some(cmd) => emit_command(&cmd),
+        None => {
+            emit_nothing();
+            Ok(())
+        }
+    };
 }
 
-/// Finds all aliases that contain all given search strings
-fn find_aliases(alias_parts: &Vec<String>, search_path: Option<PathBuf>) -> Vec<String> {
-    if search_path.is_none() {
-        return Vec::new();
+/// All actions that can be passed to Jumpcut, other than alias names
+const RESERVED_KEYWORDS: [&str; 29] = [
+    "is_exec_action", "__complete", "list", "ui",
+    "add", "addwd", "addpath", "addlast", "addshr",
+    "edit", "desc", "confirm", "rm", "cp", "cpshr", "mv", "promote", "demote", "trust", "sync", "export", "import",
+    "import-shell", "import-dirs", "suggest", "migrate", "init", "completions", "stats"];
+
+/// Reserved keywords that are not meant to be typed by users
+const HIDDEN_KEYWORDS: [&str; 2] = ["is_exec_action", "__complete"];
+
+/// Reserved keywords whose first argument is the name of an existing alias
+const ALIAS_KEYWORDS: [&str; 10] = ["edit", "desc", "confirm", "rm", "cp", "cpshr", "mv", "promote", "demote", "trust"];
+
+/// Removes the option `name` and its value (`name VALUE` or `name=VALUE`) from the command-line arguments, and returns its value
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, JumpcutError> {
+    let prefix = format!("{}=", name);
+    if let Some(i) = args.iter().position(|arg| arg.starts_with(&prefix)) {
+        return Ok(Some(args.remove(i)[prefix.len()..].to_string()));
+    }
+    if let Some(i) = args.iter().position(|arg| arg == name) {
+        if i + 1 >= args.len() {
+            return Err(JumpcutError::InvalidArgument(format!("{} requires a value.", name)));
+        }
+        args.remove(i);
+        return Ok(Some(args.remove(i)));
     }
+    return Ok(None);
+}
 
-    let search_path_val = search_path.unwrap();
-    let matches = search_path_val.read_dir().unwrap().filter_map(|entry| {
-        let fname_str = osstr_to_string(entry.unwrap().file_name().as_os_str());
+/// Parses the value of a command-line option, if it was given
+fn parse_option<T: std::str::FromStr>(value: Option<String>, name: &str) -> Result<Option<T>, JumpcutError> {
+    return match value {
+        Some(value) => value.parse().map(Some)
+            .map_err(|_| JumpcutError::InvalidArgument(format!("invalid value {:?} for {}.", value, name))),
+        None => Ok(None)
+    };
+}
 
-        let match_found = alias_parts.iter().all(|alias_part| {fname_str.contains(alias_part)});
-        return if match_found {Some(fname_str)} else {None};
-    });
+/// Removes the flag `name` from the command-line arguments, and returns whether it was present
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    let len = args.len();
+    args.retain(|arg| arg != name);
+    return args.len() != len;
+}
 
-    let mut match_vec: Vec<String> = matches.collect();
-    match_vec.sort();
-    return match_vec;
+/// Is `action` a reserved keyword or is it an alias name?
+fn is_reserved_keyword(action: &str) -> bool {
+    return RESERVED_KEYWORDS.contains(&action);
 }
 
-/// Given the name of an alias, determine where its alias file is stored, and read it
-fn load_alias(alias: String) -> Option<Alias> {
-    let in_default_path = alias_path().join(&alias).exists();
-    let in_shared_path = match alias_shared_path() {
-        Some(x) => x.join(&alias).exists(),
-        None => false
+/// Determine the command of a new alias, given the remaining command-line arguments of `j add`
+/// 
+/// The command is either given directly as arguments, read from stdin (if the only argument is "-"),
+/// or read from a file (if the arguments are "--file PATH"). The latter two can contain multiple lines.
+fn command_from_args(cmd_args: &[String]) -> Result<String, JumpcutError> {
+    let cmd = if cmd_args.len() == 1 && cmd_args[0] == "-" {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else if cmd_args.len() == 2 && cmd_args[0] == "--file" {
+        fs::read_to_string(&cmd_args[1])?
+    } else {
+        cmd_args.join(" ")
     };
 
-    if !in_default_path && !in_shared_path {
-        return None;
+    let cmd = cmd.trim_end().to_string();
+    if cmd.trim().is_empty() {
+        return Err(JumpcutError::InvalidArgument("the alias command is empty.".to_string()));
     }
+    return Ok(cmd);
+}
 
-    let mut chosen_path = alias_path();
-    if in_default_path && in_shared_path {
-        let selection = Select::with_theme(&dialoguer_theme())
-            .default(0)
-            .items(&vec![&alias, &format!("{} (shared)", &alias)])
-            .interact_opt()
-            .unwrap().unwrap();
-        if selection == 1 {
-            chosen_path = alias_shared_path().unwrap();
+/// Given the name of an alias, determine which store contains it, and read it
+/// 
+/// If several stores contain an alias with that name, the store with the highest precedence is used,
+/// unless conflicts are configured to be resolved by asking the user to choose one.
+fn load_alias(alias: &str) -> Result<(FsStore, Alias), JumpcutError> {
+    let mut stores: Vec<FsStore> = default_stores()?.into_iter().filter(|store| store.contains(alias)).collect();
+    if stores.is_empty() {
+        return Err(JumpcutError::NotFound(alias.to_string()));
+    }
+
+    let mut selection = 0;
+    if stores.len() > 1 && conflict_rule()? == ConflictRule::Ask {
+        let items: Vec<String> = stores.iter().map(|store| alias_label(alias, store)).collect();
+        if !is_interactive() {
+            return Err(JumpcutError::Ambiguous(alias.to_string(), items));
         }
-    } else if in_shared_path {
-        chosen_path = alias_shared_path().unwrap();
+        selection = Select::with_theme(&dialoguer_theme())
+            .default(0)
+            .items(&items[..])
+            .interact_opt()?
+            .ok_or(JumpcutError::Cancelled)?;
     }
+    let store = stores.swap_remove(selection);
+    let al = store.get(alias)?.ok_or_else(|| JumpcutError::NotFound(alias.to_string()))?;
+    return Ok((store, al));
+}
+
+/// Returns how an alias is displayed to the user: its name, followed by the store it's in (unless it's a personal alias)
+fn alias_label(alias: &str, store: &FsStore) -> String {
+    return if store.name() == PERSONAL_STORE {alias.to_string()} else {format!("{} ({})", alias, store.name())};
+}
 
-    return Some(Alias::read(&alias, &chosen_path.join(&alias)).unwrap());
+/// Returns the shared alias store, or an error if it hasn't been configured
+fn require_shared_store() -> Result<FsStore, JumpcutError> {
+    return shared_store()?.ok_or_else(|| JumpcutError::InvalidArgument(
+        format!("no shared storage path configured! Please set the {} environment variable.", JUMPCUT_SHARED_ENV_VAR)));
 }
 
-/// Displays a list of all aliases, together with their command and description
-fn list_aliases(alias_parts: Vec<String>) -> io::Result<()> {
-    let matches = find_aliases(&alias_parts, Some(alias_path()));
-    let matches_shared = find_aliases(&alias_parts, alias_shared_path());
+/// Returns the store of the project in the current directory; if there is none, a new project store is created in the current directory
+fn project_store_or_new() -> Result<FsStore, JumpcutError> {
+    if let Some(store) = project_store() {
+        return Ok(store);
+    }
+    let path = env::current_dir()?.join(PROJECT_FOLDER);
+    fs::create_dir_all(&path)?;
+    println!("Created a new project alias folder: {}", path.display());
+    return Ok(FsStore::new(PROJECT_STORE, path));
+}
 
-    let all_matches = matches.iter().chain(matches_shared.iter());
+/// Displays a list of all aliases, together with their command and description, grouped by store
+/// 
+/// Stores are listed in order of precedence. Aliases that are shadowed by an alias in an earlier store are marked as such.
+fn list_aliases(alias_parts: Vec<String>) -> Result<(), JumpcutError> {
+    let stores = default_stores()?;
+    let mut matches = Vec::new();
+    for store in stores.iter() {
+        matches.push(store.search(&alias_parts)?);
+    }
 
     // Find the length of the longest alias; we need this for formatting the output
-    let alias_len = all_matches.fold(0, |current_max, name| {
+    let alias_len = matches.iter().flatten().fold(0, |current_max, name| {
         return if name.len() > current_max {
             name.len()
         } else {
@@ -201,187 +429,888 @@ fn list_aliases(alias_parts: Vec<String>) -> io::Result<()> {
         } 
     });
 
-    for entry in matches {
-        let path = alias_path().join(&entry);
-        let al = Alias::read(&entry, &path)?;
-        println!("{}", al.to_string(alias_len));
-    }
-
-    if matches_shared.len() != 0 {
-        println!("\nAliases in shared folder: ({})\n", alias_shared_path().unwrap_or_default().to_str().unwrap())
-    }
-    for entry in matches_shared {
-        let path = alias_shared_path().unwrap().join(&entry);
-        let al = Alias::read(&entry, &path)?;
-        println!("{}", al.to_string(alias_len));
+    let mut printed_any = false;
+    for (i, (store, names)) in stores.iter().zip(matches.iter()).enumerate() {
+        if names.is_empty() {
+            continue;
+        }
+        // (The personal aliases don't need a header, if they're listed first)
+        if printed_any || store.name() != PERSONAL_STORE {
+            let read_only = if store.is_read_only() {" [read-only]"} else {""};
+            println!("{}Aliases in {} folder: ({}){}\n", if printed_any {"\n"} else {""}, store.name(), store.path().display(), read_only);
+        }
+        for entry in names {
+            let al = Alias::read(entry, &store.alias_file(entry))?;
+            match stores[..i].iter().find(|other| other.contains(entry)) {
+                Some(other) => println!("{} [shadowed by {}]", al.to_string(alias_len), alias_label(entry, other)),
+                None => println!("{}", al.to_string(alias_len))
+            }
+        }
+        printed_any = true;
     }
     return Ok(());
 }
 
 /// Given (part) of an alias name, find any matches and execute it
 /// 
-/// If there are multiple matches, ask the user to choose one.
-fn find_and_exec_alias(alias_parts: Vec<String>, args_map: HashMap<String, String>) -> io::Result<()> {
-    // If the user entered a full alias name
-    if alias_parts.len()==1 {
-        let alias = &alias_parts[0];
-        let path = alias_path().join(alias);
-        if path.exists() {
-            exec_alias(alias, args_map, alias_path())?;
-            return Ok(());
-        }
-    }
-
-    // If the user entered parts of an alias name
-    let mut matches = find_aliases(&alias_parts, Some(alias_path()));
-    let shared_matches = find_aliases(&alias_parts, alias_shared_path());
-    let total_matches = matches.len()+shared_matches.len();
-
-    match total_matches {
-        0 => {
-            exec_nothing();
-            error("no matching aliases found.")?;
-        },
-        1 => {
-            exec_alias(if matches.len() == 1 {&matches[0]} else {&shared_matches[0]} ,
-                       args_map,
-                       if shared_matches.len() > 0 {alias_shared_path().unwrap()} else {alias_path()})?;
-        },
-        _ => {
-            // Multiple matches; ask the user to choose
-            let shared_matches_suffixed = shared_matches.iter().map(|x| format!("{} (shared)", x));
-            let matches_len = matches.len();
-            matches.extend(shared_matches_suffixed);
+/// Alias names are matched fuzzily (see `resolve`). If there are multiple matches and none of them
+/// clearly stands out, ask the user to choose one. (The best matches are listed first.)
+/// Returns the command that should be evaluated by the shell.
+fn find_and_exec_alias(alias_parts: Vec<String>, args_map: HashMap<String, String>) -> Result<String, JumpcutError> {
+    let stores = default_stores()?;
+    let stats = UsageStats::load(&stats_path()).unwrap_or_default();
+    let options = ResolveOptions{auto_pick_frecent: frecency_auto_pick(), on_conflict: conflict_rule()?};
+    let candidate = match resolve(&stores, &alias_parts, &stats, options)? {
+        Resolution::NotFound => return Err(JumpcutError::NotFound(alias_parts.join(" "))),
+        Resolution::Unique(candidate) => candidate,
+        Resolution::Ambiguous(mut candidates) => {
+            // Multiple matches, and none of them clearly stands out; ask the user to choose
+            let items: Vec<String> = candidates.iter().map(|c| alias_label(&c.name, &stores[c.store])).collect();
+            if !is_interactive() {
+                return Err(JumpcutError::Ambiguous(alias_parts.join(" "), items));
+            }
             let selection = Select::with_theme(&dialoguer_theme())
                 .default(0)
-                .items(&matches[..])
-                .interact_opt()
-                .unwrap().unwrap();
-            if selection >= matches_len {
-                exec_alias(&shared_matches[selection-matches_len],
-                           args_map,
-                           alias_shared_path().unwrap())?;
-            } else {
-                exec_alias(&matches[selection], args_map, alias_path())?;
-            }
+                .items(&items[..])
+                .interact_opt()?
+                .ok_or(JumpcutError::Cancelled)?;
+            candidates.swap_remove(selection)
         }
-    }
-    return Ok(());
+    };
+    return exec_alias(&candidate.name, args_map, &stores[candidate.store]);
 }
 
-/// If an expected argument value was not provided, prompt for it
-fn missing_argument_handler(alias: &Alias, arg_name: &str) -> String {
-    let input = Input::<String>::new().with_prompt(
-        &format!("Enter a value for ?[{}] in `{}`", arg_name, &alias.get_command()[..])).interact().unwrap_or_default();
-    return input
+/// Should the most frequently/recently used match be executed without asking, if it clearly stands out?
+fn frecency_auto_pick() -> bool {
+    return env_flag(JUMPCUT_AUTO_PICK_ENV_VAR);
+}
+
+/// What to do if several stores contain an alias with the same name (see `Config::on_conflict`)
+fn conflict_rule() -> Result<ConflictRule, JumpcutError> {
+    return Ok(Config::load_default()?.on_conflict);
+}
+
+/// If an expected argument value was not provided (or is invalid), prompt for it
+fn missing_argument_handler(alias: &Alias, param: &Param, rejection: Option<&str>) -> Result<String, JumpcutError> {
+    if let Some(msg) = rejection {
+        error(&format!("invalid value for {}: {}", param.display_name(), msg));
+    }
+    let prompt = format!("Enter a value for {} in `{}`", param.display_name(), alias.command_preview());
+    if !is_interactive() {
+        return Err(JumpcutError::InputRequired(format!("no value provided for {} in alias {:?}.", param.display_name(), alias.get_alias())));
+    }
+    if !param.choices.is_empty() {
+        let default = param.default.as_ref()
+            .and_then(|default| param.choices.iter().position(|choice| choice == default))
+            .unwrap_or(0);
+        let selection = Select::with_theme(&dialoguer_theme()).with_prompt(prompt).default(default).items(&param.choices).interact_opt()?;
+        return Ok(param.choices[selection.ok_or(JumpcutError::Cancelled)?].clone());
+    }
+
+    let mut input = Input::<String>::new();
+    input.with_prompt(prompt).validate_with(|val: &String| param.validate(val));
+    if let Some(default) = &param.default {
+        input.default(default.clone());
+    }
+    return Ok(input.interact()?);
 }
 
 /// Execute the given alias, using the given arguments
-fn exec_alias(alias: &str, args: HashMap<String, String>, storage_path: PathBuf) -> io::Result<()> {
-    let path = storage_path.join(alias);
-    let al = Alias::read(&alias, &path)?;
-    match al.get_confirmation_level(){
-        0 => {al.execute(args, &missing_argument_handler)},
+/// 
+/// Returns the command that should be evaluated by the shell.
+fn exec_alias(alias: &str, args: HashMap<String, String>, store: &FsStore) -> Result<String, JumpcutError> {
+    let path = store.alias_file(alias);
+    let contents = fs::read_to_string(&path)?;
+    check_trust(alias, store, &contents, "execute")?;
+    let al = Alias::parse(alias, &path, &contents)?;
+    if matches!(al.get_confirmation_level(), 1 | 2) && !is_interactive() {
+        return Err(JumpcutError::InputRequired(format!("alias {:?} needs to be confirmed, but prompts are disabled.", alias)));
+    }
+    let cmd = match al.get_confirmation_level(){
+        0 => al.execute(args, &missing_argument_handler)?,
         1 => {
             if Confirm::new().default(false).with_prompt(&format!("Execute alias \"{}\"?", alias)[..]).interact()? {
-                al.execute(args, &missing_argument_handler);
+                al.execute(args, &missing_argument_handler)?
             } else {
-                exec_nothing();
+                return Err(JumpcutError::Cancelled);
             }
         },
         2 => {
             let input = Input::<String>::new().with_prompt(&format!("Type \"{}\" to confirm", alias)[..]).interact()?;
             if input==alias {
-                al.execute(args, &missing_argument_handler);
+                al.execute(args, &missing_argument_handler)?
             } else {
-                error("unexpected input.")?;
-                exec_nothing();
+                error("unexpected input.");
+                return Err(JumpcutError::Cancelled);
             }
         }
-        _ => {
+        level => return Err(JumpcutError::InvalidArgument(
+            format!("alias {:?} has an unknown confirmation level ({}); it must be 0, 1 or 2.", alias, level)))
+    };
+
+    // Failing to update the statistics shouldn't prevent the alias from being executed
+    update_stats(|stats| stats.record(&path, stats::now())).ok();
+    return Ok(cmd);
+}
+
+/// Create a new alias, and save it to file
+fn add_alias(alias: &str, cmd: &str, store: &FsStore) -> Result<(), JumpcutError> {
+    return put_new_alias(&Alias::new(alias, cmd, "", 0, store.alias_file(alias)), store);
+}
 
+/// Validate a new alias, and save it to the given store (asking the user first if that would overwrite an existing alias)
+fn put_new_alias(al: &Alias, store: &FsStore) -> Result<(), JumpcutError> {
+    let alias = al.get_alias();
+    if is_reserved_keyword(alias) {
+        return Err(JumpcutError::Reserved(alias.to_string()));
+    }
+    al.validate().map_err(JumpcutError::InvalidArgument)?;
+
+    if store.contains(alias) {
+        if !is_interactive() {
+            return Err(JumpcutError::AlreadyExists(alias.to_string()));
+        }
+        if !Confirm::new().with_prompt("Overwrite existing alias?").interact()? {
+            return Err(JumpcutError::Cancelled);
         }
     }
+    store.put(al)?;
+    remember_trusted(alias, store).ok();
     return Ok(());
 }
 
-/// If no alias can be executed, we execute an empty command instead.
-fn exec_nothing() {
-    println!(" ");
+/// Add an alias for the last command(s) in the shell's history
+///
+/// Interactively, the user chooses one of the last 10 commands (or any of the last `count` commands), and can turn
+/// its arguments into parameters. Otherwise, the last `count` commands (by default 1) are used as-is.
+fn add_last_command(alias: &str, count: Option<usize>) -> Result<(), JumpcutError> {
+    let (path, history) = read_shell_history()?;
+
+    let commands = if !is_interactive() {
+        recent_commands(&history, count.unwrap_or(1), &jumpcut_names())
+    } else if let Some(count) = count {
+        let recent = recent_commands(&history, count, &jumpcut_names());
+        let selection = MultiSelect::with_theme(&dialoguer_theme())
+            .with_prompt("Which commands do you want to include in the alias?")
+            .items(&recent)
+            .defaults(&vec![true; recent.len()])
+            .interact()?;
+        recent.into_iter().enumerate().filter(|(i, _)| selection.contains(i)).map(|(_, cmd)| cmd).collect()
+    } else {
+        let mut recent = recent_commands(&history, 10, &jumpcut_names());
+        recent.reverse();
+        let selection = Select::with_theme(&dialoguer_theme())
+            .with_prompt("Which command do you want to add as an alias?")
+            .default(0)
+            .items(&recent)
+            .interact_opt()?
+            .ok_or(JumpcutError::Cancelled)?;
+        vec![recent.remove(selection)]
+    };
+    if commands.is_empty() {
+        return Err(JumpcutError::InvalidArgument(format!("no commands found in {}.", path.display())));
+    }
+
+    // (Each command goes on its own line, as joining them with `;` breaks commands that end in `&` or a comment)
+    let cmd = commands.join("\n");
+    let cmd = if is_interactive() {add_placeholders(&cmd)?} else {cmd};
+    return add_alias(alias, &cmd, &personal_store());
 }
 
-/// Create a new alias, and save it to file
-fn add_alias(alias: &str, cmd: &str, storage_path: &Path) -> io::Result<()> {
-    if is_reserved_keyword(alias) {
-        return error(&format!("\"{}\" cannot be used as an alias name; it is a reserved keyword.", alias));
+/// Reads the history file of the shell Jumpcut is used from, and returns its location and commands (from oldest to newest)
+fn read_shell_history() -> Result<(PathBuf, Vec<String>), JumpcutError> {
+    let shell = Shell::detect();
+    let path = history_file(shell).ok_or_else(|| JumpcutError::InvalidArgument(
+        format!("reading the history of {} is not supported.", shell.name())))?;
+    if !path.is_file() {
+        return Err(JumpcutError::InvalidArgument(format!(
+            "could not find your {} history at {}; set JUMPCUT_HISTFILE to its location.", shell.name(), path.display())));
     }
+    let history = read_history(shell, &path)?;
+    return Ok((path, history));
+}
 
-    let alias_path = storage_path.join(alias);
-    let al = Alias::new(alias.clone(), cmd.clone(), "", 0, alias_path.clone());
+/// Returns the names by which Jumpcut can be called from the shell: the wrapper function's, and the binary's
+fn jumpcut_names() -> Vec<String> {
+    let mut names = vec!["j".to_string(), "jumpcut".to_string()];
+    names.extend(env::var(init::JUMPCUT_NAME_ENV_VAR).ok().filter(|x| !x.is_empty()));
+    names.extend(env::current_exe().ok().and_then(|exe| exe.file_stem().and_then(|name| name.to_str()).map(|name| name.to_string())));
+    return names;
+}
 
-    if alias_path.exists() {
-        if Confirm::new().with_prompt("Overwrite existing alias?").interact()? {
-            return al.write(&alias_path);
-        } else {
-            return Ok(());
+/// Show which commands and directories from the shell history could use an alias, and let the user add them
+fn suggest_aliases(min_count: usize, top: usize) -> Result<(), JumpcutError> {
+    let (path, history) = read_shell_history()?;
+    let stores = default_stores()?;
+    let is_taken = |name: &str| is_reserved_keyword(name) || stores.iter().any(|store| store.contains(name));
+    let mut suggestions = suggest(&history, &all_alias_commands(&stores), min_count, &jumpcut_names(), &is_taken);
+    suggestions.truncate(top);
+    if suggestions.is_empty() {
+        println!("No suggestions; none of the {} commands in {} are used {} times or more without an alias", history.len(), path.display(), min_count);
+        return Ok(());
+    }
+
+    let width = suggestions.iter().map(|s| s.name.len()).max().unwrap_or_default();
+    let items: Vec<String> = suggestions.iter()
+        .map(|s| format!("{:width$}  {}  {}", s.name, s.command, style(format!("(used {} times)", s.count)).dim(), width = width))
+        .collect();
+    if !is_interactive() {
+        println!("Suggested aliases, based on {}:", path.display());
+        for item in items {
+            println!("  {}", item);
         }
-    } else {
-        return al.write(&alias_path);
+        return Ok(());
+    }
+
+    let selection = MultiSelect::with_theme(&dialoguer_theme())
+        .with_prompt("Which aliases do you want to add? (Press space to select, enter to confirm)")
+        .items(&items)
+        .interact()?;
+    let store = personal_store();
+    for suggestion in selection.into_iter().map(|i| &suggestions[i]) {
+        let name = Input::<String>::with_theme(&dialoguer_theme())
+            .with_prompt(format!("Name for `{}`", suggestion.command))
+            .default(suggestion.name.clone())
+            .interact_text()?;
+        add_alias(&name, &suggestion.command, &store)?;
     }
+    return Ok(());
 }
 
-fn copy_alias(alias_source: &str, alias_target: &str, target_folder: PathBuf) -> io::Result<()> {
-    return match load_alias(alias_source.to_string()) {
-        Some(al) => {
-            let copied_command = al.fill_in_parameters(
-                al.get_command().to_string(), HashMap::new(),
-                &fill_in_argument_handler, false);
-            add_alias(alias_target, &copied_command, &target_folder).ok();
-            add_description(alias_target, al.get_description()).ok();
-            set_confirmation(alias_target, al.get_confirmation_level()).ok();
-            return Ok(());
-        },
-        None => error(&format!("alias {:?} does not exist.", alias_source))
-    };
+/// Let the user choose which of a command's arguments should become parameters
+fn add_placeholders(cmd: &str) -> Result<String, JumpcutError> {
+    let arguments = literal_arguments(cmd);
+    let mut values: Vec<String> = Vec::new();
+    for argument in arguments.iter() {
+        if !values.contains(&argument.text) {
+            values.push(argument.text.clone());
+        }
+    }
+    if values.is_empty() {
+        return Ok(cmd.to_string());
+    }
+    let selection = MultiSelect::with_theme(&dialoguer_theme())
+        .with_prompt("Which arguments should be parameters? (Press space to select, enter to confirm)")
+        .items(&values)
+        .interact()?;
+
+    let mut names: Vec<String> = Vec::new();
+    let mut replacements = Vec::new();
+    for value in selection.into_iter().map(|i| &values[i]) {
+        let taken = names.clone();
+        let name = Input::<String>::with_theme(&dialoguer_theme())
+            .with_prompt(format!("Parameter name for {}", value))
+            .default(suggest_param_name(value, &names))
+            .validate_with(|name: &String| if Param::parse(name).map(|param| param.name == *name && !name.is_empty()) != Some(true) {
+                Err("only letters, digits and underscores are allowed")
+            } else if taken.contains(name) {
+                Err("that name is already used")
+            } else {
+                Ok(())
+            })
+            .interact_text()?;
+        for argument in arguments.iter().filter(|argument| argument.text == *value) {
+            replacements.push((argument.clone(), name.clone()));
+        }
+        names.push(name);
+    }
+    return Ok(parameterize(cmd, &replacements));
+}
+
+fn copy_alias(alias_source: &str, alias_target: &str, target_store: &FsStore) -> Result<(), JumpcutError> {
+    let (source_store, al) = load_alias(alias_source)?;
+    // (Copies are trusted, so the original has to be trusted first)
+    let contents = fs::read_to_string(source_store.alias_file(alias_source))?;
+    check_trust(alias_source, &source_store, &contents, "copy")?;
+    let copied_command = al.fill_in_parameters(
+        al.get_command().to_string(), HashMap::new(),
+        &fill_in_argument_handler, false)?;
+    let copy = Alias::new(alias_target, &copied_command, al.get_description(), al.get_confirmation_level(),
+        target_store.alias_file(alias_target));
+    return put_new_alias(&copy, target_store);
 }
 
 /// When copying an alias, the user can choose to fill in an argument, or not
-fn fill_in_argument_handler(alias: &Alias, arg_name: &str) -> String {
+fn fill_in_argument_handler(alias: &Alias, param: &Param, _rejection: Option<&str>) -> Result<String, JumpcutError> {
+    if !is_interactive() {
+        return Ok("".to_string());
+    }
     let input = Input::<String>::new().with_prompt(
-        &format!("Enter a value for ?[{}] in `{}` (or leave empty to keep it as an argument) ", arg_name, &alias.get_command()[..])).default("".to_string()).interact().unwrap_or_default();
-    return input
+        format!("Enter a value for {} in `{}` (or leave empty to keep it as an argument) ", param.display_name(), alias.command_preview()))
+        .default("".to_string())
+        .validate_with(|val: &String| if val.is_empty() {Ok(())} else {param.validate(val)})
+        .interact()?;
+    return Ok(input);
 }
 
 /// Add/change the description of an existing alias, and save it to file
-fn add_description(alias: &str, description: &str) -> io::Result<()> {
+fn add_description(alias: &str, description: &str) -> Result<(), JumpcutError> {
     return modify_alias(alias, |al|{
-        return al.update_description(description.clone());
+        return al.update_description(description);
     });
 }
 
 /// Update whether a confirmation prompt should be shown for an existing alias, and save it to file
-fn set_confirmation(alias: &str, confirm: i8) -> io::Result<()> {
+fn set_confirmation(alias: &str, confirm: i8) -> Result<(), JumpcutError> {
     return modify_alias(alias, |al|{
         return al.update_confirm(confirm);
     });
 }
 
 /// Read an existing alias file, apply a modification function to it, and store the changes
-fn modify_alias(alias: &str, modify_fn:impl Fn(Alias) -> Alias) -> io::Result<()> {
-    return match load_alias(alias.to_string()) {
-        Some(x) => {
-            let fp = &x.get_file_path().clone();
-            return modify_fn(x).write(&fp);
-        },
-        None => error(&format!("alias {:?} does not exist.", alias))
-    };
+fn modify_alias(alias: &str, modify_fn:impl Fn(Alias) -> Alias) -> Result<(), JumpcutError> {
+    let (store, al) = load_alias(alias)?;
+    return put_modified_alias(&modify_fn(al), &store);
+}
+
+/// Save a modified version of an existing alias; if the alias was trusted, the modified version is trusted as well
+fn put_modified_alias(al: &Alias, store: &FsStore) -> Result<(), JumpcutError> {
+    let was_trusted = is_trusted(al.get_alias(), store);
+    store.put(al)?;
+    if was_trusted {
+        remember_trusted(al.get_alias(), store).ok();
+    }
+    return Ok(());
+}
+
+/// Move an alias to another store and/or rename it, keeping all of its metadata and usage statistics
+/// 
+/// If the target store already contains an alias named `new_name`, the user is asked whether to overwrite it.
+fn move_alias(alias: &str, source: &FsStore, new_name: &str, target: &FsStore) -> Result<(), JumpcutError> {
+    if !source.contains(alias) {
+        return Err(JumpcutError::NotFound(alias_label(alias, source)));
+    }
+    if is_reserved_keyword(new_name) {
+        return Err(JumpcutError::Reserved(new_name.to_string()));
+    }
+    if source.path() == target.path() && alias == new_name {
+        return Ok(());
+    }
+
+    if target.name() == PERSONAL_STORE {
+        // (Personal aliases are always trusted, so the alias has to be trusted before it becomes one)
+        let contents = fs::read_to_string(source.alias_file(alias))?;
+        check_trust(alias, source, &contents, "move")?;
+    }
+
+    let target_label = alias_label(new_name, target);
+    let overwrite = target.contains(new_name);
+    if overwrite {
+        if !is_interactive() {
+            return Err(JumpcutError::AlreadyExists(target_label));
+        }
+        if !Confirm::new().with_prompt(format!("Overwrite existing alias {}?", target_label)).interact()? {
+            return Err(JumpcutError::Cancelled);
+        }
+    }
+
+    source.move_alias(alias, target, new_name, overwrite)?;
+    update_stats(|stats| {
+        if overwrite {
+            stats.remove(&target.alias_file(new_name));
+        }
+        stats.rename(&source.alias_file(alias), &target.alias_file(new_name));
+    }).ok();
+    if source.name() == PERSONAL_STORE {
+        remember_trusted(new_name, target).ok();
+    } else {
+        update_trust(|db| db.rename(&source.alias_file(alias), &target.alias_file(new_name))).ok();
+    }
+    println!("Moved {} to {}", alias_label(alias, source), target_label);
+    return Ok(());
 }
 
 /// Remove the file of an existing alias
-fn remove_alias(alias: &str) -> io::Result<()> {
-    return match load_alias(alias.to_string()) {
-        Some(x) => {
-            fs::remove_file(x.get_file_path())},
-        None => error(&format!("alias {:?} does not exist.", alias))
+fn remove_alias(alias: &str) -> Result<(), JumpcutError> {
+    let (store, _) = load_alias(alias)?;
+    store.remove(alias)?;
+    update_stats(|stats| stats.remove(&store.alias_file(alias))).ok();
+    update_trust(|db| db.remove(&store.alias_file(alias))).ok();
+    return Ok(());
+}
+
+/// Before executing an alias that isn't a personal alias (or turning it into one), make sure the user trusts its current version
+/// 
+/// If the alias is new, or has changed since the user last trusted it, it is shown (as a diff against the trusted version)
+/// and the user is asked to approve it before Jumpcut goes on to `action` it (e.g. "execute").
+fn check_trust(alias: &str, store: &FsStore, contents: &str, action: &str) -> Result<(), JumpcutError> {
+    if store.name() == PERSONAL_STORE {
+        return Ok(());
+    }
+    let path = store.alias_file(alias);
+    let mut db = TrustDb::load(&trust_path())?;
+    let label = alias_label(alias, store);
+    let previous = match db.status(&path, contents) {
+        TrustStatus::Trusted => return Ok(()),
+        TrustStatus::New => None,
+        TrustStatus::Changed(previous) => Some(previous)
+    };
+    let reason = if previous.is_some() {"has changed since you last trusted it"} else {"is new"};
+    if !is_interactive() {
+        return Err(JumpcutError::InputRequired(format!(
+            "alias {} {}; review it, then run `j trust {}` to approve it.", label, reason, alias)));
+    }
+
+    eprintln!("Alias {} {} ({}):\n", label, reason, path.display());
+    for line in diff_lines(previous.as_deref().unwrap_or_default(), contents) {
+        match line {
+            DiffLine::Same(line) => eprintln!("  {}", line),
+            DiffLine::Removed(line) => eprintln!("{}", style(format!("- {}", line)).red()),
+            DiffLine::Added(line) => eprintln!("{}", style(format!("+ {}", line)).green())
+        }
+    }
+    eprintln!();
+    if !Confirm::new().default(false).with_prompt(format!("Trust and {} this alias?", action)).interact()? {
+        return Err(JumpcutError::Cancelled);
+    }
+    db.trust(&path, contents);
+    return db.save(&trust_path());
+}
+
+/// Approve the current version of an alias, so it can be executed without asking (see `check_trust`)
+fn trust_alias(alias: &str) -> Result<(), JumpcutError> {
+    let (store, _) = load_alias(alias)?;
+    if store.name() == PERSONAL_STORE {
+        println!("{} is a personal alias; personal aliases are always trusted.", alias);
+        return Ok(());
+    }
+    remember_trusted(alias, &store)?;
+    println!("Trusted {}", alias_label(alias, &store));
+    return Ok(());
+}
+
+/// Is the current version of an alias trusted? (Personal aliases always are.)
+fn is_trusted(alias: &str, store: &FsStore) -> bool {
+    if store.name() == PERSONAL_STORE {
+        return true;
+    }
+    let path = store.alias_file(alias);
+    return match (TrustDb::load(&trust_path()), fs::read_to_string(&path)) {
+        (Ok(db), Ok(contents)) => db.status(&path, &contents) == TrustStatus::Trusted,
+        _ => false
+    };
+}
+
+/// Trust the current version of an alias (e.g. because the user just wrote it), unless it's a personal alias
+fn remember_trusted(alias: &str, store: &FsStore) -> Result<(), JumpcutError> {
+    if store.name() == PERSONAL_STORE {
+        return Ok(());
+    }
+    let path = store.alias_file(alias);
+    let contents = fs::read_to_string(&path)?;
+    return update_trust(|db| db.trust(&path, &contents));
+}
+
+/// Read the trust database, apply a modification function to it, and store the changes
+fn update_trust(modify_fn: impl FnOnce(&mut TrustDb)) -> Result<(), JumpcutError> {
+    let mut db = TrustDb::load(&trust_path())?;
+    modify_fn(&mut db);
+    return db.save(&trust_path());
+}
+
+/// Returns the alias store with the given name (or the personal store if no name is given)
+fn store_by_name(name: Option<&str>) -> Result<FsStore, JumpcutError> {
+    return match name {
+        Some(name) => default_stores()?.into_iter().find(|store| store.name() == name)
+            .ok_or_else(|| JumpcutError::InvalidArgument(format!("there is no alias store named {:?}.", name))),
+        None => Ok(personal_store())
+    };
+}
+
+/// Prints a bundle (see `Bundle`) containing all aliases of a store (whose names contain each of the search strings)
+fn export_aliases(alias_parts: Vec<String>, store_name: Option<&str>) -> Result<(), JumpcutError> {
+    let store = store_by_name(store_name)?;
+    let bundle = Bundle::export(&store, &store.search(&alias_parts)?)?;
+    println!("{}", bundle.to_json()?);
+    return Ok(());
+}
+
+/// Imports the aliases in a bundle file ("-" for stdin) into a store, and shows what was (or with `dry_run`, would be) done
+fn import_aliases(file: &str, store_name: Option<&str>, prefix: &str, on_conflict: OnConflict, dry_run: bool) -> Result<(), JumpcutError> {
+    let store = store_by_name(store_name)?;
+    let contents = if file == "-" {
+        let mut buffer = String::new();
+        io::stdin().read_to_string(&mut buffer)?;
+        buffer
+    } else {
+        fs::read_to_string(file)?
+    };
+    let bundle = Bundle::parse(&PathBuf::from(file), &contents)?;
+    let conflict_handler = |alias: &str| {
+        if dry_run {
+            return Ok(OnConflict::Ask);
+        }
+        return import_conflict_handler(alias, &store);
+    };
+    let actions = bundle.plan_import(&store, prefix, on_conflict, &conflict_handler)?;
+
+    let (mut added, mut changed, mut skipped) = (Vec::new(), Vec::new(), Vec::new());
+    for action in actions {
+        match action {
+            ImportAction::Add(al) | ImportAction::Change(al) if is_reserved_keyword(al.get_alias()) => {
+                skipped.push(format!("{} (it is a reserved keyword)", al.get_alias()));
+            },
+            ImportAction::Add(al) => {
+                if !dry_run {
+                    store.put(&al)?;
+                }
+                added.push(al.get_alias().to_string());
+            },
+            ImportAction::Change(al) => {
+                if !dry_run {
+                    store.put(&al)?;
+                }
+                changed.push(al.get_alias().to_string());
+            },
+            ImportAction::Skip(alias, reason) => skipped.push(format!("{} ({})", alias, reason))
+        }
+    }
+
+    let verb = |past: &str, future: &str| if dry_run {format!("Would {}", future)} else {past.to_string()};
+    for (items, label) in [(added, verb("Added", "add")), (changed, verb("Changed", "change")), (skipped, verb("Skipped", "skip"))] {
+        if !items.is_empty() {
+            println!("{} {} alias(es) in {}:", label, items.len(), store.name());
+            for item in items {
+                println!("  {}", item);
+            }
+        }
+    }
+    return Ok(());
+}
+
+/// Create aliases for the aliases, functions and abbreviations in a shell's configuration file
+fn import_shell_aliases(shell_name: &str, file: &str, store_name: Option<&str>) -> Result<(), JumpcutError> {
+    let shell = match Shell::from_name(shell_name) {
+        Some(Shell::Nushell) | None => return Err(JumpcutError::InvalidArgument(
+            format!("cannot import from {:?}; choose one of bash, zsh, fish or powershell.", shell_name))),
+        Some(shell) => shell
     };
+    let store = store_by_name(store_name)?;
+    let contents = fs::read_to_string(file)?;
+    let parsed = parse_shell_config(shell, &contents);
+
+    let mut imported = Vec::new();
+    let mut failed: Vec<(String, usize, String)> = parsed.unconverted.into_iter().map(|x| (x.name, x.line, x.reason)).collect();
+    for found in parsed.aliases {
+        let al = Alias::new(&found.name, &found.command, &found.description, 0, store.alias_file(&found.name));
+        match put_new_alias(&al, &store) {
+            Ok(_) => imported.push(found.name),
+            Err(JumpcutError::Reserved(_)) => failed.push((found.name, found.line, "it is a reserved keyword".to_string())),
+            Err(JumpcutError::AlreadyExists(_)) => failed.push((found.name, found.line, "an alias with that name already exists".to_string())),
+            Err(JumpcutError::Cancelled) => failed.push((found.name, found.line, "kept the existing alias".to_string())),
+            Err(JumpcutError::InvalidArgument(reason)) => failed.push((found.name, found.line, reason)),
+            Err(e) => return Err(e)
+        }
+    }
+
+    println!("Imported {} alias(es) into {}{}", imported.len(), store.name(), if imported.is_empty() {""} else {":"});
+    for alias in imported.iter() {
+        println!("  {}", alias);
+    }
+    if !failed.is_empty() {
+        failed.sort_by_key(|(_, line, _)| *line);
+        println!("Could not import:");
+        for (alias, line, reason) in failed {
+            let alias = if alias.is_empty() {"(unknown)".to_string()} else {alias};
+            println!("  {} (line {}): {}", alias, line, reason);
+        }
+    }
+    return Ok(());
+}
+
+/// Create `addpath`-style aliases for the most frequently-used directories in the database of zoxide, z, autojump or fasd
+fn import_dirs(tool_name: &str, file: Option<PathBuf>, top: Option<usize>, min_score: Option<f64>) -> Result<(), JumpcutError> {
+    let tool = DirTool::from_name(tool_name).ok_or_else(|| JumpcutError::InvalidArgument(
+        format!("cannot import from {:?}; choose one of zoxide, z, autojump or fasd.", tool_name)))?;
+    let path = file.or_else(|| tool.default_database()).unwrap_or_default();
+    if !path.is_file() {
+        return Err(JumpcutError::InvalidArgument(
+            format!("no {} database found at {}; use --file to choose another location.", tool.name(), path.display())));
+    }
+
+    let stores = default_stores()?;
+    let commands = all_alias_commands(&stores);
+    let mut entries = Vec::new();
+    let mut unsafe_paths = 0;
+    for entry in tool.read_database(&path)? {
+        if min_score.map(|min_score| entry.score < min_score) == Some(true) || !entry.path.is_dir() {
+            continue;
+        }
+        // (Skip directories that already have an alias)
+        match cd_command(&absolute_path(&entry.path)?) {
+            Some(cmd) if !commands.contains(&cmd) => entries.push(entry),
+            Some(_) => {},
+            None => unsafe_paths += 1
+        }
+    }
+    if unsafe_paths > 0 {
+        println!("Skipped {} director(ies) whose path contains quotes, `$`, backquotes or backslashes", unsafe_paths);
+    }
+    entries.truncate(top.unwrap_or(entries.len()));
+    let is_taken = |name: &str| is_reserved_keyword(name) || stores.iter().any(|store| store.contains(name));
+    let mut proposals = propose_names(&entries, &is_taken);
+    if proposals.is_empty() {
+        println!("No new directories to import from {}", tool.name());
+        return Ok(());
+    }
+
+    if is_interactive() {
+        let items: Vec<String> = proposals.iter().map(|(name, entry)| format!("{}  {}", style(name).bold(), entry.path.display())).collect();
+        let selection = MultiSelect::with_theme(&dialoguer_theme())
+            .with_prompt("Which directories do you want to add as aliases?")
+            .items(&items)
+            .defaults(&vec![true; items.len()])
+            .interact()?;
+        proposals = proposals.into_iter().enumerate().filter(|(i, _)| selection.contains(i)).map(|(_, proposal)| proposal).collect();
+    }
+
+    let store = personal_store();
+    for (name, entry) in proposals.iter() {
+        if let Some(cmd) = cd_command(&absolute_path(&entry.path)?) {
+            add_alias(name, &cmd, &store)?;
+        }
+    }
+    println!("Added {} alias(es):", proposals.len());
+    for (name, entry) in proposals {
+        println!("  {}  {}", name, entry.path.display());
+    }
+    return Ok(());
+}
+
+/// Returns the commands of all aliases in the given stores (ignoring any that can't be read)
+fn all_alias_commands(stores: &[FsStore]) -> Vec<String> {
+    let mut commands = Vec::new();
+    for store in stores {
+        for name in store.list().unwrap_or_default() {
+            if let Ok(Some(al)) = store.get(&name) {
+                commands.push(al.get_command().to_string());
+            }
+        }
+    }
+    return commands;
+}
+
+/// If an imported alias has the same name as an existing alias, ask the user what to do
+fn import_conflict_handler(alias: &str, store: &FsStore) -> Result<OnConflict, JumpcutError> {
+    if !is_interactive() {
+        return Err(JumpcutError::InputRequired(format!(
+            "alias {} already exists; use --on-conflict to choose what to do with existing aliases.", alias_label(alias, store))));
+    }
+    let choices = [OnConflict::Skip, OnConflict::Overwrite, OnConflict::Rename];
+    let selection = Select::with_theme(&dialoguer_theme())
+        .with_prompt(format!("{} already exists; what do you want to do?", alias_label(alias, store)))
+        .default(0)
+        .items(&["Keep the existing alias", "Overwrite it", "Import it under another name"])
+        .interact_opt()?
+        .ok_or(JumpcutError::Cancelled)?;
+    return Ok(choices[selection]);
+}
+
+/// Synchronize an alias store (by default, the shared store) with its git remote, and show what changed
+fn sync_store(store_name: Option<&str>) -> Result<(), JumpcutError> {
+    let store = match store_name {
+        Some(_) => store_by_name(store_name)?,
+        None => require_shared_store()?
+    };
+    let report = sync(&store, &sync_conflict_handler)?;
+
+    if !report.committed.is_empty() {
+        println!("Committed your changes:");
+        print_changes(&report.committed);
+    }
+    if !report.pulled.is_empty() {
+        println!("Changes by others:");
+        print_changes(&report.pulled);
+    }
+    if report.pushed {
+        println!("Pushed your changes to the remote repository");
+    }
+    if report.committed.is_empty() && report.pulled.is_empty() && !report.pushed {
+        println!("The {} aliases are up to date", store.name());
+    }
+    return Ok(());
+}
+
+fn print_changes(changes: &[Change]) {
+    for change in changes {
+        match change.kind {
+            ChangeKind::Added => println!("  {} {}", style("+").green(), change.alias),
+            ChangeKind::Changed => println!("  {} {}", style("~").yellow(), change.alias),
+            ChangeKind::Removed => println!("  {} {}", style("-").red(), change.alias)
+        }
+    }
+}
+
+/// If an alias was changed both locally and remotely, ask the user which version to keep
+fn sync_conflict_handler(alias: &str) -> Result<ConflictChoice, JumpcutError> {
+    if !is_interactive() {
+        return Err(JumpcutError::InputRequired(format!("alias {:?} was changed both locally and remotely.", alias)));
+    }
+    let selection = Select::with_theme(&dialoguer_theme())
+        .with_prompt(format!("{} was changed both locally and remotely; which version do you want to keep?", alias))
+        .default(0)
+        .items(&["Your version", "Their version"])
+        .interact_opt()?
+        .ok_or(JumpcutError::Cancelled)?;
+    return Ok(if selection == 0 {ConflictChoice::Ours} else {ConflictChoice::Theirs});
+}
+
+/// Rewrite all alias files in the legacy format to the structured format, in all alias stores
+fn migrate_stores() -> Result<(), JumpcutError> {
+    for store in default_stores()? {
+        if store.is_read_only() {
+            println!("Skipping read-only store {} ({})", store.name(), store.path().display());
+            continue;
+        }
+        migrate_store(&store)?;
+    }
+    return Ok(());
+}
+
+/// Rewrite all legacy alias files in the given store
+/// 
+/// Before anything is changed, the entire store is copied to a backup folder next to it.
+fn migrate_store(store: &FsStore) -> Result<(), JumpcutError> {
+    let store_path = store.path();
+    let all_aliases = store.list()?;
+    let mut legacy_aliases = Vec::new();
+    for alias in all_aliases.iter() {
+        if is_legacy_format(&fs::read_to_string(store_path.join(alias))?) {
+            legacy_aliases.push(alias);
+        }
+    }
+
+    if legacy_aliases.is_empty() {
+        println!("Nothing to migrate in {}", store_path.display());
+        return Ok(());
+    }
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let mut backup_name = store_path.file_name().unwrap_or_default().to_os_string();
+    backup_name.push(format!(".backup-{}", timestamp));
+    let backup_path = store_path.with_file_name(backup_name);
+    fs::create_dir_all(&backup_path)?;
+    for alias in all_aliases.iter() {
+        fs::copy(store_path.join(alias), backup_path.join(alias))?;
+    }
+
+    for alias in legacy_aliases.iter() {
+        let path = store_path.join(alias);
+        Alias::read(alias, &path)?.write(&path)?;
+    }
+    println!("Migrated {} alias(es) in {} (backup: {})", legacy_aliases.len(), store_path.display(), backup_path.display());
+    return Ok(());
+}
+
+/// Parse the arguments of `init` and `completions`
+/// 
+/// `shell_args` consists of the shell's name, optionally followed by `--name NAME` to change the wrapper function's name.
+/// Returns the shell, the function name and the path to the Jumpcut binary.
+fn parse_shell_args(shell_args: &[String]) -> Result<(Shell, String, String), JumpcutError> {
+    let shell = Shell::from_name(&shell_args[0]).ok_or_else(|| JumpcutError::InvalidArgument(
+        format!("unsupported shell {:?}; choose one of bash, zsh, fish, powershell or nushell.", shell_args[0])))?;
+
+    let name = match &shell_args[1..] {
+        [] => "j",
+        [flag, name] if flag == "--name" => name,
+        [flag] if flag.starts_with("--name=") => &flag["--name=".len()..],
+        _ => return Err(JumpcutError::InvalidArgument("unexpected arguments; expected SHELL [--name NAME]".to_string()))
+    };
+    if !init::is_valid_function_name(name) {
+        return Err(JumpcutError::InvalidArgument(format!("{:?} cannot be used as a function name.", name)));
+    }
+
+    let bin = absolute_path(&env::current_exe()?)?;
+    return Ok((shell, name.to_string(), bin));
+}
+
+/// Returns the shell completion candidates for the word that is currently being typed
+/// 
+/// `words` are the words after the name of the wrapper function; the last one is the word being completed.
+fn complete_words(words: &[String]) -> Vec<String> {
+    let current = words.last().map(|x| x.as_str()).unwrap_or_default();
+    let alias_names = || {
+        let mut names: Vec<String> = default_stores().unwrap_or_default().iter()
+            .flat_map(|store| store.search(&[current.to_string()]).unwrap_or_default())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    };
+
+    if words.len() <= 1 {
+        let mut candidates: Vec<String> = RESERVED_KEYWORDS.iter()
+            .filter(|keyword| !HIDDEN_KEYWORDS.contains(keyword) && keyword.starts_with(current))
+            .map(|keyword| keyword.to_string())
+            .collect();
+        candidates.extend(alias_names());
+        return candidates;
+    }
+
+    let action = words[0].as_str();
+    if words.len() == 2 && ALIAS_KEYWORDS.contains(&action) {
+        return alias_names();
+    }
+    if words.len() == 2 && (action == "init" || action == "completions") {
+        return ["bash", "zsh", "fish", "powershell", "nushell"].iter()
+            .filter(|shell| shell.starts_with(current))
+            .map(|shell| shell.to_string())
+            .collect();
+    }
+    if is_reserved_keyword(action) || !current.starts_with("--") {
+        return Vec::new();
+    }
+
+    // Complete the named parameters of the alias being invoked
+    let al = match find_alias_for_completion(action) {
+        Some(x) => x,
+        None => return Vec::new()
+    };
+    let mut candidates = Vec::new();
+    for param in al.params() {
+        let flag = format!("--{}=", param.name);
+        if param.name.parse::<usize>().is_err() && flag.starts_with(current) && !candidates.contains(&flag) {
+            candidates.push(flag);
+        }
+    }
+    return candidates;
+}
+
+/// Find the alias that (part of) an alias name refers to, without prompting the user
+fn find_alias_for_completion(alias_part: &str) -> Option<Alias> {
+    let stores = default_stores().ok()?;
+    let options = ResolveOptions{on_conflict: conflict_rule().ok()?, ..ResolveOptions::default()};
+    return match resolve(&stores, &[alias_part.to_string()], &UsageStats::default(), options).ok()? {
+        Resolution::Unique(candidate) => stores[candidate.store].get(&candidate.name).ok()?,
+        _ => None
+    };
+}
+
+/// Read the usage statistics, apply a modification function to them, and store the changes
+fn update_stats(modify_fn: impl FnOnce(&mut UsageStats)) -> Result<(), JumpcutError> {
+    let mut stats = UsageStats::load(&stats_path())?;
+    modify_fn(&mut stats);
+    return stats.save(&stats_path());
+}
+
+/// Displays all aliases, from most to least used
+fn show_stats() -> Result<(), JumpcutError> {
+    let stats = UsageStats::load(&stats_path())?;
+    let now = stats::now();
+
+    let mut entries = Vec::new();
+    for store in default_stores()? {
+        for name in store.list()? {
+            let usage = stats.get(&store.alias_file(&name));
+            entries.push((alias_label(&name, &store), usage));
+        }
+    }
+    entries.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(b.1.last_used.cmp(&a.1.last_used)).then(a.0.cmp(&b.0)));
+
+    let width = entries.iter().map(|(label, _)| label.len()).max().unwrap_or_default();
+    for (label, usage) in entries {
+        let last_used = if usage.count == 0 {"never used".to_string()} else {format!("last used {}", stats::format_age(usage.last_used, now))};
+        println!("{: <w$}  {: >5}x  {}", accent_style().apply_to(label), usage.count, last_used, w=width);
+    }
+    return Ok(());
 }
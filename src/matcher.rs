@@ -0,0 +1,108 @@
+/// Score for each matched character
+const MATCH_SCORE: i64 = 16;
+/// Bonus for matching the first character of the name
+const PREFIX_BONUS: i64 = 16;
+/// Bonus for matching the first character of a word in the name (e.g. the "p" in "git-push" or "gitPush")
+const BOUNDARY_BONUS: i64 = 12;
+/// Bonus for matching a character right after the previously matched one
+const CONSECUTIVE_BONUS: i64 = 10;
+/// Penalty for each skipped character between two matched characters
+const GAP_PENALTY: i64 = 2;
+/// Bonus for matching the entire name
+const EXACT_BONUS: i64 = 1000;
+
+/// How well does `query` match `name`? (case-insensitive)
+///
+/// All characters of `query` must occur in `name`, in the same order, but not necessarily consecutively.
+/// (e.g. "gps" matches "git-push") Returns None if there is no match; otherwise a higher score is a better match.
+/// Matches at the start of the name or of a word in the name, and consecutive characters, score higher.
+pub fn score(name: &str, query: &str) -> Option<i64> {
+  let name_chars: Vec<char> = name.chars().collect();
+  let name_lower: Vec<char> = name.to_lowercase().chars().collect();
+  let query_lower: Vec<char> = query.to_lowercase().chars().collect();
+  if query_lower.is_empty() {
+    return Some(0);
+  }
+  if name_lower.len() != name_chars.len() || query_lower.len() > name_lower.len() {
+    // (Lowercasing changed the length; fall back to a plain substring match)
+    return if name.to_lowercase().contains(&query.to_lowercase()) {Some(MATCH_SCORE * query.len() as i64)} else {None};
+  }
+
+  let char_bonus = |j: usize| -> i64 {
+    if j == 0 {
+      return PREFIX_BONUS;
+    }
+    let prev = name_chars[j-1];
+    let cur = name_chars[j];
+    let is_boundary = !prev.is_alphanumeric() || (prev.is_lowercase() && cur.is_uppercase())
+      || (prev.is_alphabetic() && cur.is_numeric());
+    return if is_boundary {BOUNDARY_BONUS} else {0};
+  };
+
+  // best[j] is the best score for matching the query so far, where the last matched query character is at position j
+  let mut best: Vec<Option<i64>> = name_lower.iter().enumerate()
+    .map(|(j, c)| if *c == query_lower[0] {Some(MATCH_SCORE + char_bonus(j))} else {None})
+    .collect();
+  for q in query_lower[1..].iter() {
+    let mut next = vec![None; name_lower.len()];
+    for j in 0..name_lower.len() {
+      if name_lower[j] != *q {
+        continue;
+      }
+      next[j] = (0..j).filter_map(|k| best[k].map(|prev_score| {
+        let transition = if k + 1 == j {CONSECUTIVE_BONUS} else {-GAP_PENALTY * (j - k - 1) as i64};
+        prev_score + transition + MATCH_SCORE + char_bonus(j)
+      })).max();
+    }
+    best = next;
+  }
+
+  let exact_bonus = if name_lower == query_lower {EXACT_BONUS} else {0};
+  return best.into_iter().flatten().max().map(|x| x.max(1) + exact_bonus);
+}
+
+/// How well do all of the `query_parts` match `name`? (see `score`)
+///
+/// Returns None unless each of the parts matches.
+pub fn score_all(name: &str, query_parts: &[String]) -> Option<i64> {
+  let mut total = 0;
+  for part in query_parts.iter() {
+    total += score(name, part)?;
+  }
+  return Some(total);
+}
+
+/// Given match scores in descending order, is the best one clearly better than all others?
+pub fn is_dominant(sorted_scores: &[i64]) -> bool {
+  return match sorted_scores {
+    [] => false,
+    [_] => true,
+    [best, second, ..] => *best >= 2 * *second
+  };
+}
+
+/// Matches alias names against a query, consisting of one or more parts (see `score_all`)
+pub struct Matcher {
+  query_parts: Vec<String>
+}
+
+impl Matcher {
+  /// Constructor
+  pub fn new(query_parts: &[String]) -> Matcher {
+    return Matcher{query_parts: query_parts.to_vec()};
+  }
+
+  /// How well does the query match `name`? Returns None if it doesn't match at all.
+  pub fn score(&self, name: &str) -> Option<i64> {
+    return score_all(name, &self.query_parts);
+  }
+
+  /// Returns the names that match the query with their scores, best matches first (ties are sorted by name)
+  pub fn rank(&self, names: impl IntoIterator<Item = String>) -> Vec<(i64, String)> {
+    let mut ranked: Vec<(i64, String)> = names.into_iter()
+      .filter_map(|name| self.score(&name).map(|s| (s, name)))
+      .collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    return ranked;
+  }
+}
@@ -0,0 +1,122 @@
+use std::path::Path;
+use regex::Regex;
+
+/// The type of value that a parameter accepts
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ParamType {
+  Text,
+  Int,
+  Path
+}
+
+/// A parameter placeholder in an alias command
+///
+/// The full placeholder syntax is `?[!name:type|choice1,choice2=default]`, where each part except the name is optional:
+/// - `!`        : the value is inserted as-is, rather than quoted as a single shell word
+/// - `:type`    : the type of value, either `int`, `path` or `text` (the default)
+/// - `|choices` : a comma-separated list of allowed values
+/// - `=default` : the value to use if none is provided
+///
+/// Positional parameters (?1, ?2, ..) are parameters named "1", "2", etc. without any of the optional parts.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Param {
+  pub name: String,
+  pub raw: bool,
+  pub param_type: ParamType,
+  pub choices: Vec<String>,
+  pub default: Option<String>
+}
+
+impl Param {
+  /// Constructor for a plain text parameter
+  pub fn new(name: &str) -> Param {
+    return Param {
+      name: name.to_string(),
+      raw: false,
+      param_type: ParamType::Text,
+      choices: Vec::new(),
+      default: None
+    };
+  }
+
+  /// Parses the contents of a placeholder (the part between the `?[` and `]` brackets)
+  ///
+  /// Returns None if the placeholder is not well-formed, or if its default value doesn't match its type or choices.
+  pub fn parse(spec: &str) -> Option<Param> {
+    let re = Regex::new(r"^(!)?([A-Za-z0-9_]*)(?::([a-z]+))?(?:\|([^=]*))?(?:=(.*))?$").unwrap();
+    let caps = re.captures(spec)?;
+
+    let param_type = match caps.get(3).map(|x| x.as_str()) {
+      None | Some("text") => ParamType::Text,
+      Some("int") => ParamType::Int,
+      Some("path") => ParamType::Path,
+      Some(_) => return None
+    };
+    let choices = match caps.get(4) {
+      Some(x) => x.as_str().split(',').map(|choice| choice.trim().to_string()).collect(),
+      None => Vec::new()
+    };
+
+    let param = Param {
+      name: caps[2].to_string(),
+      raw: caps.get(1).is_some(),
+      param_type: param_type,
+      choices: choices,
+      default: caps.get(5).map(|x| x.as_str().to_string())
+    };
+    // (A path default is only checked when it's used, as it may be relative to the working directory)
+    if let Some(default) = &param.default {
+      if param.param_type != ParamType::Path && param.validate(default).is_err() {
+        return None;
+      }
+    }
+    return Some(param);
+  }
+
+  /// Is `value` a valid value for this parameter?
+  ///
+  /// If not, an error message is returned that can be shown to the user.
+  pub fn validate(&self, value: &str) -> Result<(), String> {
+    if !self.choices.is_empty() && !self.choices.iter().any(|choice| choice == value) {
+      return Err(format!("\"{}\" is not one of: {}", value, self.choices.join(", ")));
+    }
+
+    return match self.param_type {
+      ParamType::Text => Ok(()),
+      ParamType::Int => match value.parse::<i64>() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("\"{}\" is not a number", value))
+      },
+      ParamType::Path => if Path::new(value).exists() {
+        Ok(())
+      } else {
+        Err(format!("\"{}\" does not exist", value))
+      }
+    };
+  }
+
+  /// Returns how this parameter is referred to in prompts and error messages
+  pub fn display_name(&self) -> String {
+    return format!("?[{}]", self.name);
+  }
+
+  /// Describes this parameter's options, e.g. "?[port] (int, default: 8080)"
+  pub fn summary(&self) -> String {
+    let mut details = Vec::new();
+    if self.raw {
+      details.push("raw".to_string());
+    }
+    match self.param_type {
+      ParamType::Text => {},
+      ParamType::Int => details.push("int".to_string()),
+      ParamType::Path => details.push("path".to_string())
+    }
+    if !self.choices.is_empty() {
+      details.push(format!("one of: {}", self.choices.join(", ")));
+    }
+    if let Some(default) = &self.default {
+      details.push(format!("default: {}", default));
+    }
+    return if details.is_empty() {self.display_name()} else {format!("{} ({})", self.display_name(), details.join("; "))};
+  }
+}
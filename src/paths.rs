@@ -0,0 +1,40 @@
+use std::env;
+use std::path::PathBuf;
+
+/// Folder in which Jumpcut keeps its files, instead of the home directory (e.g. to run tests in isolation)
+pub const JUMPCUT_HOME_ENV_VAR: &str = "JUMPCUT_HOME";
+
+pub const JUMPCUT_SHARED_ENV_VAR: &str = "JUMPCUT_SHARED_PATH";
+
+/// Returns the folder in which Jumpcut keeps its files: JUMPCUT_HOME if set, or else the home directory
+pub fn jumpcut_home() -> PathBuf {
+  return match env::var_os(JUMPCUT_HOME_ENV_VAR).filter(|x| !x.is_empty()) {
+    Some(path) => PathBuf::from(path),
+    None => dirs::home_dir().unwrap_or_default()
+  };
+}
+
+/// Returns the path where the user's own aliases are stored
+pub fn alias_path() -> PathBuf {
+  return jumpcut_home().join(".jumpcut");
+}
+
+/// Returns the path of the file that keeps track of alias usage
+pub fn stats_path() -> PathBuf {
+  return jumpcut_home().join(".jumpcut_stats");
+}
+
+/// Returns the path of the file that keeps track of which aliases the user trusts
+pub fn trust_path() -> PathBuf {
+  return jumpcut_home().join(".jumpcut_trust");
+}
+
+/// Returns the path of Jumpcut's configuration file
+pub fn config_path() -> PathBuf {
+  return jumpcut_home().join(".jumpcut_config.toml");
+}
+
+/// Returns the shared alias storage path, if it has been configured
+pub fn alias_shared_path() -> Option<PathBuf> {
+  return env::var_os(JUMPCUT_SHARED_ENV_VAR).map(PathBuf::from);
+}
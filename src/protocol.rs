@@ -0,0 +1,32 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process;
+use jumpcut::error::JumpcutError;
+use jumpcut::init::{EXIT_EVAL, JUMPCUT_CMD_FILE_ENV_VAR};
+
+/// Hand over a command to the shell wrapper, which will evaluate it
+///
+/// If the wrapper provided a command file, the command is written to it (as a new file) and we exit with `EXIT_EVAL`.
+/// Otherwise (older wrappers that rely on `is_exec_action`), the command is printed to stdout.
+pub fn emit_command(cmd: &str) -> Result<(), JumpcutError> {
+  return match env::var_os(JUMPCUT_CMD_FILE_ENV_VAR) {
+    Some(path) => {
+      // (The file must not exist yet, so we never follow a symlink or overwrite a file someone else put there)
+      OpenOptions::new().write(true).create_new(true).open(path)?.write_all(cmd.as_bytes())?;
+      process::exit(EXIT_EVAL);
+    },
+    None => {
+      println!("{}", cmd);
+      Ok(())
+    }
+  };
+}
+
+/// Tell the shell wrapper that there is no command to evaluate
+pub fn emit_nothing() {
+  // Older wrappers always evaluate our output, and Powershell's Invoke-Expression fails on empty input
+  if env::var_os(JUMPCUT_CMD_FILE_ENV_VAR).is_none() {
+    println!(" ");
+  }
+}
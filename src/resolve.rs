@@ -0,0 +1,109 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use crate::config::ConflictRule;
+use crate::error::JumpcutError;
+use crate::matcher::{is_dominant, Matcher};
+use crate::stats::UsageStats;
+use crate::store::AliasStore;
+
+/// An alias that matches a query
+#[derive(Clone, PartialEq, Debug)]
+pub struct Candidate {
+  pub name: String,
+  pub store: usize, // index of the store that contains this alias
+  pub score: i64, // how well the alias's name matches the query (see `matcher::score`)
+  pub frecency: f64 // how frequently/recently the alias was used (see `stats::Usage::frecency`)
+}
+
+/// The result of looking up which alias a query refers to
+#[derive(Clone, PartialEq, Debug)]
+pub enum Resolution {
+  /// No alias matches the query
+  NotFound,
+  /// The query clearly refers to this alias
+  Unique(Candidate),
+  /// The query matches several aliases, none of which clearly stands out (best candidates first)
+  Ambiguous(Vec<Candidate>)
+}
+
+/// Options that influence how a query is resolved
+#[derive(Clone, Copy, Default, Debug)]
+pub struct ResolveOptions {
+  /// If the best-matching alias is also used much more frequently/recently than all other candidates, pick it
+  pub auto_pick_frecent: bool,
+  /// What to do if several stores contain an alias with the same name
+  pub on_conflict: ConflictRule
+}
+
+/// Determine which alias in `stores` the query (the parts of an alias name that the user entered) refers to
+///
+/// `stores` must be ordered by precedence (see `store::default_stores`). If the query is the exact name of an alias,
+/// the first store containing it wins. Otherwise, all aliases that (fuzzily) match the query are ranked (see `rank`).
+/// The query is unique if there is only one candidate, or if the best candidate clearly stands out.
+/// If several stores contain an alias with the same name, only the first one is considered, unless `on_conflict` is
+/// `ConflictRule::Ask`; then the query is ambiguous if it matches that name.
+pub fn resolve<S: AliasStore>(stores: &[S], query: &[String], stats: &UsageStats, options: ResolveOptions) -> Result<Resolution, JumpcutError> {
+  let now = crate::stats::now();
+  if let [name] = query {
+    let mut exact_matches: Vec<Candidate> = stores.iter().enumerate()
+      .filter(|(_, store)| store.contains(name))
+      .map(|(i, store)| Candidate{name: name.clone(), store: i, score: i64::MAX, frecency: stats.get(&store.alias_file(name)).frecency(now)})
+      .collect();
+    if exact_matches.len() == 1 || (!exact_matches.is_empty() && options.on_conflict == ConflictRule::Shadow) {
+      return Ok(Resolution::Unique(exact_matches.swap_remove(0)));
+    }
+    if !exact_matches.is_empty() {
+      return Ok(Resolution::Ambiguous(exact_matches));
+    }
+  }
+
+  let mut candidates = rank(stores, query, stats)?;
+  if options.on_conflict == ConflictRule::Shadow {
+    candidates = without_shadowed(candidates);
+  }
+  if candidates.is_empty() {
+    return Ok(Resolution::NotFound);
+  }
+  let scores: Vec<i64> = candidates.iter().map(|c| c.score).collect();
+  if is_dominant(&scores) || (options.auto_pick_frecent && is_most_frecent(&candidates)) {
+    return Ok(Resolution::Unique(candidates.swap_remove(0)));
+  }
+  return Ok(Resolution::Ambiguous(candidates));
+}
+
+/// Returns all aliases in `stores` that (fuzzily) match the query, best matches first
+///
+/// Matches are ranked by their match score, then by their frecency, then by name.
+/// (If the query is empty, all aliases match equally well.)
+pub fn rank<S: AliasStore>(stores: &[S], query: &[String], stats: &UsageStats) -> Result<Vec<Candidate>, JumpcutError> {
+  let now = crate::stats::now();
+  let matcher = Matcher::new(query);
+  let mut candidates = Vec::new();
+  for (i, store) in stores.iter().enumerate() {
+    for (score, name) in matcher.rank(store.list()?) {
+      let frecency = stats.get(&store.alias_file(&name)).frecency(now);
+      candidates.push(Candidate{name: name, store: i, score: score, frecency: frecency});
+    }
+  }
+  candidates.sort_by(|a, b| b.score.cmp(&a.score)
+    .then(b.frecency.partial_cmp(&a.frecency).unwrap_or(Ordering::Equal))
+    .then(a.name.cmp(&b.name)));
+  return Ok(candidates);
+}
+
+/// Is the first (best-matching) candidate also used much more frequently/recently than all other candidates?
+fn is_most_frecent(candidates: &[Candidate]) -> bool {
+  let best = candidates[0].frecency;
+  return best > 0.0 && candidates[1..].iter().all(|c| best >= 2.0 * c.frecency);
+}
+
+/// Removes the candidates that are shadowed by an alias with the same name in a store with a higher precedence
+fn without_shadowed(mut candidates: Vec<Candidate>) -> Vec<Candidate> {
+  let mut first_store: HashMap<String, usize> = HashMap::new();
+  for c in candidates.iter() {
+    let store = first_store.entry(c.name.clone()).or_insert(c.store);
+    *store = (*store).min(c.store);
+  }
+  candidates.retain(|c| first_store[&c.name] == c.store);
+  return candidates;
+}
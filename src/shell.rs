@@ -0,0 +1,83 @@
+use std::env;
+
+pub const JUMPCUT_SHELL_ENV_VAR: &str = "JUMPCUT_SHELL";
+
+/// The shells that Jumpcut can be used from
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Shell {
+  Posix,
+  Bash,
+  Zsh,
+  Fish,
+  PowerShell,
+  Nushell
+}
+
+impl Shell {
+  /// Looks up a shell by name (e.g. "bash", "fish", "pwsh")
+  pub fn from_name(name: &str) -> Option<Shell> {
+    return match name.to_lowercase().as_ref() {
+      "sh" | "posix" => Some(Shell::Posix),
+      "bash" => Some(Shell::Bash),
+      "zsh" => Some(Shell::Zsh),
+      "fish" => Some(Shell::Fish),
+      "powershell" | "pwsh" => Some(Shell::PowerShell),
+      "nushell" | "nu" => Some(Shell::Nushell),
+      _ => None
+    };
+  }
+
+  /// The name of this shell, as accepted by `Shell::from_name`
+  pub fn name(&self) -> &'static str {
+    return match self {
+      Shell::Posix => "sh",
+      Shell::Bash => "bash",
+      Shell::Zsh => "zsh",
+      Shell::Fish => "fish",
+      Shell::PowerShell => "powershell",
+      Shell::Nushell => "nushell"
+    };
+  }
+
+  /// Returns the shell that will execute our output
+  ///
+  /// This is taken from the JUMPCUT_SHELL environment variable, if set. Otherwise, we assume
+  /// Powershell on Windows and a POSIX shell everywhere else.
+  pub fn detect() -> Shell {
+    let default = if cfg!(windows) {Shell::PowerShell} else {Shell::Posix};
+    return match env::var(JUMPCUT_SHELL_ENV_VAR) {
+      Ok(name) => Shell::from_name(&name).unwrap_or(default),
+      Err(_) => default
+    };
+  }
+
+  /// Quotes `value` such that this shell treats it as a single word, without interpreting any of its contents
+  ///
+  /// Values that only consist of "safe" characters are returned as-is, to keep commands readable.
+  pub fn quote(&self, value: &str) -> String {
+    // (In Powershell, a comma creates an array and a leading @ splats a variable)
+    let safe_symbols = if *self == Shell::PowerShell {"_-+=%:./"} else {"_-+=@%:,./"};
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || safe_symbols.contains(c);
+    // (zsh expands `=cmd` to the path of a command at the start of a word, and with some options also after a `=`)
+    let is_posix = matches!(self, Shell::Posix | Shell::Bash | Shell::Zsh);
+    let has_equals_expansion = is_posix && (value.starts_with('=') || value.contains("=="));
+    if !value.is_empty() && value.chars().all(is_safe) && !has_equals_expansion {
+      return value.to_string();
+    }
+
+    return match self {
+      Shell::Posix | Shell::Bash | Shell::Zsh => format!("'{}'", value.replace('\'', "'\\''")),
+      Shell::Fish => format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'")),
+      Shell::PowerShell => format!("'{}'", value.replace('\'', "''")),
+      // Nushell's single-quoted strings have no escapes, so we need a raw string if the value contains a quote.
+      // A raw string ends at a quote followed by as many #'s as it started with, so use more #'s than the value has.
+      Shell::Nushell => if value.contains('\'') {
+        let longest = value.split('\'').skip(1).map(|rest| rest.len() - rest.trim_start_matches('#').len()).max().unwrap_or(0);
+        let hashes = "#".repeat(longest + 1);
+        format!("r{}'{}'{}", hashes, value, hashes)
+      } else {
+        format!("'{}'", value)
+      }
+    };
+  }
+}
@@ -0,0 +1,477 @@
+use regex::{Captures, Regex};
+use crate::shell::Shell;
+
+/// An alias (or function/abbreviation) found in a shell's configuration file, converted to Jumpcut's syntax
+#[derive(Clone, PartialEq, Debug)]
+pub struct ShellAlias {
+  pub name: String,
+  pub command: String,
+  pub description: String,
+  pub line: usize // line number where it's defined (starting from 1)
+}
+
+/// A definition that could not be converted
+#[derive(Clone, PartialEq, Debug)]
+pub struct Unconverted {
+  pub name: String,
+  pub reason: String,
+  pub line: usize
+}
+
+/// The result of parsing a shell configuration file
+#[derive(Clone, Default, Debug)]
+pub struct ShellImport {
+  pub aliases: Vec<ShellAlias>,
+  pub unconverted: Vec<Unconverted>
+}
+
+/// Finds all aliases, functions and abbreviations in the contents of a shell's configuration file (e.g. `.bashrc`)
+///
+/// Recognizes `alias` and function definitions in POSIX shells (sh, bash and zsh); `alias`, `abbr` and `function` in fish;
+/// and `Set-Alias`, `New-Alias` and `function` in PowerShell. References to the shell's positional arguments
+/// (e.g. `$1` and `$@`, `$argv` in fish or `$args` in PowerShell) are converted to Jumpcut's placeholders (`?1`, `?@`).
+/// Definitions that can't be converted are reported in `ShellImport::unconverted`.
+pub fn parse_shell_config(shell: Shell, contents: &str) -> ShellImport {
+  let lines: Vec<&str> = contents.lines().collect();
+  let mut result = ShellImport::default();
+  let mut i = 0;
+  while i < lines.len() {
+    let line = lines[i].trim();
+    let line_nr = i + 1;
+    i += 1;
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+
+    if let Some((name, mut header_rest)) = function_header(shell, line) {
+      if let Some(reason) = unsupported_function_header(shell, &header_rest) {
+        result.unconverted.push(Unconverted{name: name, reason: reason, line: line_nr});
+        i = skip_function_body(shell, &lines, i, &header_rest);
+        continue;
+      }
+      // (The opening brace may be on the next line)
+      if shell != Shell::Fish && header_rest.is_empty() && lines.get(i).map(|x| x.trim().starts_with('{')) == Some(true) {
+        header_rest = lines[i].trim().to_string();
+        i += 1;
+      }
+
+      let mut body = Vec::new();
+      let first_line = header_rest.trim_start_matches('{').trim();
+      if shell != Shell::Fish && block_depth_change(shell, &header_rest) == 0 {
+        // A function on a single line, e.g. `up() { cd ..; }`
+        body.push(first_line.trim_end_matches('}').trim().trim_end_matches(';').trim());
+      } else {
+        if shell != Shell::Fish && !first_line.is_empty() {
+          body.push(first_line);
+        }
+        let end = skip_function_body(shell, &lines, i, &header_rest);
+        for body_line in lines[i..end].iter() {
+          body.push(body_line);
+        }
+        // (Keep anything before the closing brace, e.g. `  echo done; }`)
+        if let Some(last) = body.pop() {
+          let last_trimmed = last.trim();
+          let before_end = if shell == Shell::Fish {last_trimmed.strip_suffix("end")} else {last_trimmed.strip_suffix('}')};
+          match before_end.map(|x| x.trim().trim_end_matches(';').trim()) {
+            Some(x) if !x.is_empty() => body.push(x),
+            Some(_) => {},
+            None => body.push(last) // (The end of the file was reached)
+          }
+        }
+        i = end;
+      }
+      let body_lines: Vec<&str> = body.into_iter().collect();
+      add_definition(shell, &mut result, name, &dedent(&body_lines), function_description(shell, &header_rest), line_nr, true);
+      continue;
+    }
+
+    match definitions(shell, line) {
+      Ok(definitions) => {
+        for (name, command) in definitions {
+          add_definition(shell, &mut result, name, &command, String::new(), line_nr, false);
+        }
+      },
+      Err(Some((name, reason))) => result.unconverted.push(Unconverted{name: name, reason: reason, line: line_nr}),
+      Err(None) => {}
+    }
+  }
+  return result;
+}
+
+/// Converts a definition to Jumpcut's syntax, and adds it to `result`
+fn add_definition(shell: Shell, result: &mut ShellImport, name: String, body: &str, description: String, line: usize, is_function: bool) {
+  if body.trim().is_empty() {
+    result.unconverted.push(Unconverted{name: name, reason: "its definition is empty".to_string(), line: line});
+    return;
+  }
+  match convert_positional(shell, body, is_function) {
+    Ok(command) => result.aliases.push(ShellAlias{name: name, command: command, description: description, line: line}),
+    Err(reason) => result.unconverted.push(Unconverted{name: name, reason: reason, line: line})
+  }
+}
+
+/// A definition that can't be imported (its name, if known, and why), or None if there was nothing to import
+type Rejection = Option<(String, String)>;
+
+/// Parses the alias/abbreviation definitions on a single line
+///
+/// Returns Err(None) if the line doesn't define anything, or an error (the name, if known, and why it can't be imported).
+fn definitions(shell: Shell, line: &str) -> Result<Vec<(String, String)>, Rejection> {
+  let words = split_words(shell, line).map_err(|reason| Some((String::new(), reason)))?;
+  let (command, args) = match words.split_first() {
+    Some((command, args)) => (command.as_str(), args),
+    None => return Err(None)
+  };
+
+  return match (shell, command) {
+    (Shell::PowerShell, _) if ["set-alias", "new-alias", "sal", "nal"].contains(&command.to_lowercase().as_str()) => {
+      powershell_alias(args).map(|definition| vec![definition])
+    },
+    (Shell::Fish, "abbr") => {
+      let mut words = Vec::new();
+      let mut iter = args.iter();
+      while let Some(arg) = iter.next() {
+        match arg.as_str() {
+          "-p" | "--position" => {
+            iter.next();
+          },
+          "-r" | "--regex" | "-f" | "--function" => {
+            let name = iter.find(|x| !x.starts_with('-')).cloned().unwrap_or_default();
+            return Err(Some((name, format!("abbreviation option {} is not supported", arg))));
+          },
+          arg if arg.starts_with('-') => {},
+          _ => words.push(arg)
+        }
+      }
+      match words.split_first() {
+        Some((name, expansion)) if !expansion.is_empty() => {
+          Ok(vec![(name.to_string(), expansion.iter().map(|x| x.as_str()).collect::<Vec<&str>>().join(" "))])
+        },
+        _ => Err(None)
+      }
+    },
+    (Shell::Fish, "alias") if args.len() >= 2 && !args[0].contains('=') => {
+      Ok(vec![(args[0].clone(), args[1..].join(" "))])
+    },
+    (Shell::Fish, "alias") | (Shell::Posix, "alias") | (Shell::Bash, "alias") | (Shell::Zsh, "alias") => {
+      let mut definitions = Vec::new();
+      for arg in args {
+        if arg.starts_with('-') {
+          // (e.g. global or suffix aliases in zsh, which can't be expressed as a command)
+          let name = args.iter().find_map(|x| x.split_once('=').map(|(name, _)| name.to_string())).unwrap_or_default();
+          return Err(Some((name, format!("alias option {} is not supported", arg))));
+        }
+        match arg.split_once('=') {
+          Some((name, value)) => definitions.push((name.to_string(), value.to_string())),
+          None => return Err(None) // (Prints the definition of an existing alias)
+        }
+      }
+      Ok(definitions)
+    },
+    _ => Err(None)
+  };
+}
+
+/// Parses the arguments of PowerShell's Set-Alias / New-Alias: [-Name] NAME [-Value] VALUE
+fn powershell_alias(args: &[String]) -> Result<(String, String), Rejection> {
+  let (mut name, mut value) = (None, None);
+  let mut positional = Vec::new();
+  let mut iter = args.iter();
+  while let Some(arg) = iter.next() {
+    match arg.to_lowercase().as_str() {
+      "-name" => name = iter.next().cloned(),
+      "-value" => value = iter.next().cloned(),
+      "-force" | "-passthru" => {},
+      option if option.starts_with('-') => {
+        iter.next(); // (Options such as -Scope or -Description take a value)
+      },
+      _ => positional.push(arg.clone())
+    }
+  }
+  let mut positional = positional.into_iter();
+  let name = name.or_else(|| positional.next());
+  let value = value.or_else(|| positional.next());
+  return match (name, value) {
+    (Some(name), Some(value)) => Ok((name, value)),
+    (Some(name), None) => Err(Some((name, "the alias has no value".to_string()))),
+    _ => Err(None)
+  };
+}
+
+/// If `line` starts a function definition, returns the function's name and the remainder of the line
+fn function_header(shell: Shell, line: &str) -> Option<(String, String)> {
+  let regex = match shell {
+    Shell::Fish => r"^function\s+([^\s;]+)(?P<rest>.*)$",
+    Shell::PowerShell => r"(?i)^function\s+([A-Za-z0-9_.:-]+)\s*(?P<rest>\{.*)?$",
+    _ => r"^(?:function\s+([^\s(){}]+)\s*(?:\(\s*\))?|([^\s(){}=]+)\s*\(\s*\))\s*(?P<rest>\{.*)?$"
+  };
+  let caps = Regex::new(regex).unwrap().captures(line)?;
+  let name = caps.get(1).or_else(|| caps.get(2))?.as_str().to_string();
+  let rest = caps.name("rest").map(|x| x.as_str().trim()).unwrap_or_default();
+  return Some((name, rest.to_string()));
+}
+
+/// Returns the index of the line after the end of a function, given the index of the first line of its body
+fn skip_function_body(shell: Shell, lines: &[&str], start: usize, header_rest: &str) -> usize {
+  let mut depth = if shell == Shell::Fish {1} else {block_depth_change(shell, header_rest)};
+  if depth <= 0 {
+    return start;
+  }
+  for (i, line) in lines.iter().enumerate().skip(start) {
+    depth += block_depth_change(shell, line.trim());
+    if depth <= 0 {
+      return i + 1;
+    }
+  }
+  return lines.len();
+}
+
+/// If a function's header declares named arguments (which can't be converted), returns an explanation
+fn unsupported_function_header(shell: Shell, header_rest: &str) -> Option<String> {
+  if shell != Shell::Fish {
+    return None;
+  }
+  let words = split_words(shell, header_rest).unwrap_or_default();
+  if words.iter().any(|word| word == "-a" || word.starts_with("--argument")) {
+    return Some("the function has named arguments, which can't be converted".to_string());
+  }
+  return None;
+}
+
+/// Returns the description of a fish function (`function NAME --description DESC`)
+fn function_description(shell: Shell, header_rest: &str) -> String {
+  if shell != Shell::Fish {
+    return String::new();
+  }
+  let words = split_words(shell, header_rest).unwrap_or_default();
+  for (i, word) in words.iter().enumerate() {
+    if word == "-d" || word == "--description" {
+      return words.get(i + 1).cloned().unwrap_or_default();
+    }
+    if let Some(description) = word.strip_prefix("--description=") {
+      return description.to_string();
+    }
+  }
+  return String::new();
+}
+
+/// How much a line opens (positive) or closes (negative) code blocks
+///
+/// For fish, this counts block keywords and `end`; for other shells, this counts braces (outside of quotes).
+fn block_depth_change(shell: Shell, line: &str) -> i32 {
+  if shell == Shell::Fish {
+    let first_word = line.split_whitespace().next().unwrap_or_default();
+    return match first_word {
+      "if" | "for" | "while" | "switch" | "function" | "begin" => 1,
+      "end" => -1,
+      _ => 0
+    };
+  }
+  let mut depth = 0;
+  let mut quote = None;
+  let mut escaped = false;
+  for c in line.chars() {
+    match (quote, c) {
+      _ if escaped => escaped = false,
+      (Some('\''), '\'') | (Some('"'), '"') => quote = None,
+      (Some(_), _) => {},
+      (None, '\\') => escaped = true,
+      (None, '\'') | (None, '"') => quote = Some(c),
+      (None, '#') => break,
+      (None, '{') => depth += 1,
+      (None, '}') => depth -= 1,
+      _ => {}
+    }
+  }
+  return depth;
+}
+
+/// Removes the common indentation of the given lines, and joins them
+fn dedent(lines: &[&str]) -> String {
+  let indent = lines.iter().filter(|line| !line.trim().is_empty())
+    .map(|line| line.len() - line.trim_start().len())
+    .min().unwrap_or_default();
+  let lines: Vec<&str> = lines.iter().map(|line| if line.len() >= indent {&line[indent..]} else {line.trim_start()}).collect();
+  return lines.join("\n").trim_end().to_string();
+}
+
+/// Converts references to the shell's positional arguments to Jumpcut placeholders (?1, ?@, ..)
+///
+/// References in single-quoted strings are left alone, as the shell doesn't expand them either. Only references that
+/// are a separate word (or a separate double-quoted string) can be converted, as Jumpcut quotes the placeholder's value
+/// itself. If the command uses the arguments in a way that can't be expressed with placeholders (e.g. `"Hello $1"`),
+/// an explanation is returned.
+fn convert_positional(shell: Shell, command: &str, is_function: bool) -> Result<String, String> {
+  // (`unsupported` is checked before converting, `leftover` afterwards)
+  let (reference, unsupported, leftover) = match shell {
+    Shell::Fish => (
+      r#""?\$argv\[([1-9][0-9]*)\]"?|"?\$argv\b"?"#,
+      r"\$argv\[[^\]]*[^0-9\]]|\bcount \$argv|\breturn\b",
+      r"\$argv"),
+    Shell::PowerShell => (
+      r#"(?i)"?\$args\[([0-9]+)\]"?|"?[$@]args\b"?"#,
+      r"(?i)\$args\[[^\]]*[^0-9\]]|\$args\.|\bparam\s*\(|\breturn\b",
+      r"(?i)[$@]args\b"),
+    _ => (
+      r#""?\$(?:([1-9])|\{([1-9][0-9]*)\}|([@*])|\{([@*])\})"?"#,
+      r"\$#|\$\{#|\$0|\$\{[0-9@*]+[^0-9@*}]|\bshift\b|\blocal\b|\breturn\b",
+      r"\$\{?[0-9@*#]")
+  };
+  let reference = Regex::new(reference).unwrap();
+  let unsupported = Regex::new(unsupported).unwrap();
+  let leftover = Regex::new(leftover).unwrap();
+  let what = if is_function {"function"} else {"alias"};
+
+  // (An escaped `$` isn't a reference; it's temporarily replaced by a character that can't occur in a command)
+  let escaped_dollar = if shell == Shell::PowerShell {"`$"} else {"\\$"};
+  let mut out = String::new();
+  let parts = split_single_quoted(shell, command);
+  let last = parts.len().saturating_sub(1);
+  for (i, (text, is_literal)) in parts.into_iter().enumerate() {
+    if is_literal {
+      out.push_str(text);
+      continue;
+    }
+    let text = &text.replace(escaped_dollar, "\0");
+    if let Some(m) = unsupported.find(text) {
+      return Err(format!("the {} uses `{}`, which can't be converted", what, m.as_str().trim()));
+    }
+    let mut embedded = None;
+    let converted = reference.replace_all(text, |caps: &Captures| {
+      let m = caps.get(0).unwrap();
+      let whole = m.as_str();
+      let index = caps.get(1).or_else(|| caps.get(2)).map(|x| x.as_str());
+      let all = caps.get(3).or_else(|| caps.get(4)).map(|x| x.as_str());
+      let placeholder = match (shell, index, all) {
+        (Shell::PowerShell, Some(index), _) => format!("?{}", index.parse::<usize>().unwrap_or_default() + 1),
+        (_, Some(index), _) => format!("?{}", index),
+        (_, None, Some(all)) => format!("?{}", all),
+        (_, None, None) => "?@".to_string()
+      };
+      // (Quotes that enclose the entire reference are removed, as Jumpcut quotes the placeholder's value itself)
+      let opening = whole.len() > 1 && whole.starts_with('"');
+      let closing = whole.len() > 1 && whole.ends_with('"');
+      // (The parts before and after this one are single-quoted strings, so they're part of the same word)
+      let is_separate = |c: Option<char>, is_edge: bool| match c {
+        Some(c) => c.is_whitespace() || ";|&()<>".contains(c),
+        None => is_edge
+      };
+      if opening != closing || in_double_quotes(shell, &text[..m.start()])
+        || !is_separate(text[..m.start()].chars().last(), i == 0) || !is_separate(text[m.end()..].chars().next(), i == last) {
+        embedded.get_or_insert_with(|| whole.trim_matches('"').to_string());
+        return whole.to_string();
+      }
+      return placeholder;
+    });
+    if let Some(embedded) = embedded {
+      return Err(format!("the {} uses `{}` within a longer word or string, which can't be converted", what, embedded));
+    }
+    if let Some(m) = leftover.find(&converted) {
+      return Err(format!("the {} uses `{}`, which can't be converted", what, m.as_str()));
+    }
+    out.push_str(&converted.replace('\0', escaped_dollar));
+  }
+  return Ok(out);
+}
+
+/// Does `text` end inside a double-quoted string? (`text` must not contain single-quoted strings)
+fn in_double_quotes(shell: Shell, text: &str) -> bool {
+  let escape = if shell == Shell::PowerShell {'`'} else {'\\'};
+  let mut inside = false;
+  let mut escaped = false;
+  for c in text.chars() {
+    if escaped {
+      escaped = false;
+    } else if c == escape {
+      escaped = true;
+    } else if c == '"' {
+      inside = !inside;
+    }
+  }
+  return inside;
+}
+
+/// Splits a command into parts, marking which parts are single-quoted strings (in which nothing is expanded)
+fn split_single_quoted(shell: Shell, command: &str) -> Vec<(&str, bool)> {
+  let mut parts = Vec::new();
+  let mut start = 0;
+  let mut in_single = false;
+  let mut in_double = false;
+  let mut escaped = false;
+  for (i, c) in command.char_indices() {
+    if escaped {
+      escaped = false;
+      continue;
+    }
+    match c {
+      '\\' if shell != Shell::PowerShell && (!in_single || shell == Shell::Fish) => escaped = true,
+      '`' if shell == Shell::PowerShell && !in_single => escaped = true,
+      '"' if !in_single => in_double = !in_double,
+      '\'' if !in_double => {
+        if in_single {
+          parts.push((&command[start..i + 1], true));
+          start = i + 1;
+        } else {
+          parts.push((&command[start..i], false));
+          start = i;
+        }
+        in_single = !in_single;
+      },
+      _ => {}
+    }
+  }
+  parts.push((&command[start..], in_single));
+  return parts;
+}
+
+/// Splits a line into words, like the shell would (removing quotes and escapes, and stopping at a comment)
+fn split_words(shell: Shell, line: &str) -> Result<Vec<String>, String> {
+  let escape = if shell == Shell::PowerShell {'`'} else {'\\'};
+  let mut words = Vec::new();
+  let mut word: Option<String> = None;
+  let mut chars = line.chars().peekable();
+  while let Some(c) = chars.next() {
+    match c {
+      c if c.is_whitespace() => words.extend(word.take()),
+      '#' if word.is_none() => break,
+      ';' => {
+        words.extend(word.take());
+        break; // (Only the first command on a line is considered)
+      },
+      '\'' => {
+        let w = word.get_or_insert_with(String::new);
+        loop {
+          match chars.next() {
+            None => return Err("unterminated quote".to_string()),
+            Some('\'') if shell == Shell::PowerShell && chars.peek() == Some(&'\'') => {
+              chars.next();
+              w.push('\'');
+            },
+            Some('\'') => break,
+            Some('\\') if shell == Shell::Fish && matches!(chars.peek(), Some('\'') | Some('\\')) => w.push(chars.next().unwrap()),
+            Some(c) => w.push(c)
+          }
+        }
+      },
+      '"' => {
+        let w = word.get_or_insert_with(String::new);
+        loop {
+          match chars.next() {
+            None => return Err("unterminated quote".to_string()),
+            Some('"') => break,
+            Some(c) if c == escape && matches!(chars.peek(), Some('"') | Some('\\') | Some('$') | Some('`')) => w.push(chars.next().unwrap()),
+            Some(c) => w.push(c)
+          }
+        }
+      },
+      c if c == escape => {
+        if let Some(next) = chars.next() {
+          word.get_or_insert_with(String::new).push(next);
+        }
+      },
+      c => word.get_or_insert_with(String::new).push(c)
+    }
+  }
+  words.extend(word);
+  return Ok(words);
+}
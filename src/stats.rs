@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use crate::error::JumpcutError;
+use crate::store::canonical_alias_file;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// How often and how recently an alias was used
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+pub struct Usage {
+  pub count: u64,
+  pub last_used: u64 // Unix timestamp, in seconds
+}
+
+impl Usage {
+  /// Combines `count` and `last_used` into a single number: aliases that are used often and recently score higher
+  pub fn frecency(&self, now: u64) -> f64 {
+    let age = now.saturating_sub(self.last_used);
+    let weight = if age < HOUR {
+      4.0
+    } else if age < DAY {
+      2.0
+    } else if age < WEEK {
+      0.5
+    } else {
+      0.25
+    };
+    return self.count as f64 * weight;
+  }
+}
+
+/// Usage statistics of all aliases, stored in a file separate from the aliases themselves
+///
+/// Aliases are identified by the path of their alias file.
+#[derive(Serialize, Deserialize, Default)]
+pub struct UsageStats {
+  #[serde(default)]
+  aliases: BTreeMap<String, Usage>
+}
+
+impl UsageStats {
+  /// Reads the statistics file; if it doesn't exist (yet), empty statistics are returned
+  pub fn load(path: &Path) -> Result<UsageStats, JumpcutError> {
+    if !path.exists() {
+      return Ok(UsageStats::default());
+    }
+    let contents = fs::read_to_string(path)?;
+    return toml::from_str(&contents).map_err(|e| JumpcutError::InvalidFile(path.to_path_buf(), e.to_string()));
+  }
+
+  /// Writes the statistics to file
+  pub fn save(&self, path: &Path) -> Result<(), JumpcutError> {
+    let data = toml::to_string(self).map_err(|e| JumpcutError::InvalidFile(path.to_path_buf(), e.to_string()))?;
+    fs::write(path, data)?;
+    return Ok(());
+  }
+
+  /// Returns the usage statistics of the alias stored in `alias_file` (zero if it was never used)
+  pub fn get(&self, alias_file: &Path) -> Usage {
+    return self.aliases.get(&key(alias_file)).cloned().unwrap_or_default();
+  }
+
+  /// Registers that the alias stored in `alias_file` was used at time `now`
+  pub fn record(&mut self, alias_file: &Path, now: u64) {
+    let usage = self.aliases.entry(key(alias_file)).or_default();
+    usage.count += 1;
+    usage.last_used = now;
+  }
+
+  /// Keep the statistics of an alias that was moved/renamed from `old_file` to `new_file`
+  pub fn rename(&mut self, old_file: &Path, new_file: &Path) {
+    if let Some(usage) = self.aliases.remove(&key(old_file)) {
+      self.aliases.insert(key(new_file), usage);
+    }
+  }
+
+  /// Forget about the alias stored in `alias_file`
+  pub fn remove(&mut self, alias_file: &Path) {
+    self.aliases.remove(&key(alias_file));
+  }
+}
+
+fn key(alias_file: &Path) -> String {
+  return canonical_alias_file(alias_file).to_string_lossy().to_string();
+}
+
+/// Returns the current time as a Unix timestamp, in seconds
+pub fn now() -> u64 {
+  return SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+}
+
+/// Describes how long ago a timestamp was, e.g. "3 days ago"
+pub fn format_age(timestamp: u64, now: u64) -> String {
+  let age = now.saturating_sub(timestamp);
+  let (amount, unit) = if age < HOUR {
+    (age / 60, "minute")
+  } else if age < DAY {
+    (age / HOUR, "hour")
+  } else {
+    (age / DAY, "day")
+  };
+  return format!("{} {}{} ago", amount, unit, if amount == 1 {""} else {"s"});
+}
@@ -0,0 +1,277 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::alias::Alias;
+use crate::config::Config;
+use crate::error::JumpcutError;
+use crate::paths::*;
+use crate::utils::*;
+
+/// Name of the store containing the user's own aliases
+pub const PERSONAL_STORE: &str = "personal";
+
+/// Name of the store containing aliases shared with others
+pub const SHARED_STORE: &str = "shared";
+
+/// Name of the store containing the aliases of the project in the current directory
+pub const PROJECT_STORE: &str = "project";
+
+/// Name of the folder containing a project's aliases
+pub const PROJECT_FOLDER: &str = ".jumpcut";
+
+/// A collection of aliases, identified by their names
+pub trait AliasStore {
+  /// Short name of this store, e.g. "personal" or "shared"
+  fn name(&self) -> &str;
+
+  /// Returns the names of all aliases in this store, in alphabetical order
+  fn list(&self) -> Result<Vec<String>, JumpcutError>;
+
+  /// Returns the alias with the given name, or None if it doesn't exist
+  fn get(&self, alias: &str) -> Result<Option<Alias>, JumpcutError>;
+
+  /// Stores an alias (under the name returned by `Alias::get_alias`), replacing any existing alias with that name
+  fn put(&self, alias: &Alias) -> Result<(), JumpcutError>;
+
+  /// Removes the alias with the given name; fails if it doesn't exist
+  fn remove(&self, alias: &str) -> Result<(), JumpcutError>;
+
+  /// Renames an alias; fails if it doesn't exist, or if an alias named `new_name` already exists
+  fn rename(&self, old_name: &str, new_name: &str) -> Result<(), JumpcutError>;
+
+  /// Returns the path of the file in which the given alias is (or would be) stored
+  fn alias_file(&self, alias: &str) -> PathBuf;
+
+  /// Does this store contain an alias with the given name?
+  fn contains(&self, alias: &str) -> bool {
+    return self.alias_file(alias).is_file();
+  }
+
+  /// Returns the names of all aliases that contain each of the given search strings, in alphabetical order
+  fn search(&self, alias_parts: &[String]) -> Result<Vec<String>, JumpcutError> {
+    let mut matches = self.list()?;
+    matches.retain(|name| alias_parts.iter().all(|part| name.contains(part)));
+    return Ok(matches);
+  }
+}
+
+/// An alias store that keeps each alias in a separate file, in one folder
+#[derive(Clone, Debug)]
+pub struct FsStore {
+  name: String,
+  path: PathBuf,
+  read_only: bool,
+  priority: i32
+}
+
+impl FsStore {
+  /// Constructor
+  ///
+  /// `name` : short name of this store
+  /// `path` : the folder in which the alias files are stored
+  pub fn new(name: &str, path: PathBuf) -> FsStore {
+    return FsStore{name: name.to_string(), path: path, read_only: false, priority: 0};
+  }
+
+  /// Makes this store read-only (or writable)
+  pub fn read_only(mut self, read_only: bool) -> FsStore {
+    self.read_only = read_only;
+    return self;
+  }
+
+  /// Sets this store's priority; if several stores contain an alias with the same name, the highest priority wins
+  pub fn priority(mut self, priority: i32) -> FsStore {
+    self.priority = priority;
+    return self;
+  }
+
+  /// Returns the folder in which the alias files are stored
+  pub fn path(&self) -> &Path {
+    return &self.path;
+  }
+
+  /// Is this store read-only?
+  pub fn is_read_only(&self) -> bool {
+    return self.read_only;
+  }
+
+  /// Returns this store's priority (see `priority`)
+  pub fn get_priority(&self) -> i32 {
+    return self.priority;
+  }
+
+  /// Fails if this store is read-only
+  fn check_writable(&self) -> Result<(), JumpcutError> {
+    if self.read_only {
+      return Err(JumpcutError::ReadOnly(self.name.clone()));
+    }
+    return Ok(());
+  }
+
+  /// Moves an alias to the `target` store (which may be this store), under the name `new_name`
+  ///
+  /// The alias file is moved as-is, so all of its contents are preserved. If `overwrite` is false, this fails if the
+  /// target store already contains an alias named `new_name`; otherwise, that alias is replaced.
+  /// Within the same file system, the file is moved atomically.
+  pub fn move_alias(&self, alias: &str, target: &FsStore, new_name: &str, overwrite: bool) -> Result<(), JumpcutError> {
+    self.check_writable()?;
+    target.check_writable()?;
+    if !self.contains(alias) {
+      return Err(JumpcutError::NotFound(alias.to_string()));
+    }
+    validate_alias_name(new_name)?;
+    if !overwrite && target.contains(new_name) {
+      return Err(JumpcutError::AlreadyExists(new_name.to_string()));
+    }
+
+    let source_file = self.alias_file(alias);
+    let target_file = target.alias_file(new_name);
+    fs::create_dir_all(&target.path)?;
+    if fs::rename(&source_file, &target_file).is_err() {
+      // (Renaming fails if both stores are on a different file system)
+      let tmp_file = target.path.join(format!(".{}.tmp", new_name));
+      fs::copy(&source_file, &tmp_file)?;
+      fs::rename(&tmp_file, &target_file)?;
+      fs::remove_file(&source_file)?;
+    }
+    return Ok(());
+  }
+}
+
+impl AliasStore for FsStore {
+  fn name(&self) -> &str {
+    return &self.name;
+  }
+
+  fn list(&self) -> Result<Vec<String>, JumpcutError> {
+    if !self.path.is_dir() {
+      return Ok(Vec::new());
+    }
+    let mut names = Vec::new();
+    for entry in self.path.read_dir()? {
+      let entry = entry?;
+      let name = osstr_to_string(entry.file_name().as_os_str());
+      // (Hidden files are not aliases, e.g. temporary files)
+      if entry.path().is_file() && !name.starts_with('.') {
+        names.push(name);
+      }
+    }
+    names.sort();
+    return Ok(names);
+  }
+
+  fn get(&self, alias: &str) -> Result<Option<Alias>, JumpcutError> {
+    validate_alias_name(alias)?;
+    if !self.contains(alias) {
+      return Ok(None);
+    }
+    return Alias::read(alias, &self.alias_file(alias)).map(Some);
+  }
+
+  fn put(&self, alias: &Alias) -> Result<(), JumpcutError> {
+    self.check_writable()?;
+    validate_alias_name(alias.get_alias())?;
+    fs::create_dir_all(&self.path)?;
+    return alias.write(&self.alias_file(alias.get_alias()));
+  }
+
+  fn remove(&self, alias: &str) -> Result<(), JumpcutError> {
+    self.check_writable()?;
+    validate_alias_name(alias)?;
+    if !self.contains(alias) {
+      return Err(JumpcutError::NotFound(alias.to_string()));
+    }
+    fs::remove_file(self.alias_file(alias))?;
+    return Ok(());
+  }
+
+  fn rename(&self, old_name: &str, new_name: &str) -> Result<(), JumpcutError> {
+    return self.move_alias(old_name, self, new_name, false);
+  }
+
+  fn alias_file(&self, alias: &str) -> PathBuf {
+    return self.path.join(alias);
+  }
+
+  fn contains(&self, alias: &str) -> bool {
+    return validate_alias_name(alias).is_ok() && self.alias_file(alias).is_file();
+  }
+}
+
+/// Fails if `alias` can't be used as the name of an alias file, e.g. because it contains a path separator
+/// (so an alias can never end up outside of its store's folder)
+pub fn validate_alias_name(alias: &str) -> Result<(), JumpcutError> {
+  if alias.is_empty() || alias.starts_with('.') || alias.contains('/') || alias.contains('\\') || alias.contains('\0') {
+    return Err(JumpcutError::InvalidArgument(format!("{:?} cannot be used as an alias name", alias)));
+  }
+  return Ok(());
+}
+
+/// Returns `alias_file` with its store's folder canonicalized, so that the same alias always has the same path
+/// (e.g. to look it up in the usage statistics), no matter how the store's folder was written
+///
+/// The alias file itself doesn't need to exist.
+pub fn canonical_alias_file(alias_file: &Path) -> PathBuf {
+  let folder = match alias_file.parent() {
+    Some(folder) if folder.as_os_str().is_empty() => Path::new("."),
+    Some(folder) => folder,
+    None => return alias_file.to_path_buf()
+  };
+  return match (folder.canonicalize(), alias_file.file_name()) {
+    (Ok(folder), Some(name)) => folder.join(name),
+    _ => alias_file.to_path_buf()
+  };
+}
+
+/// Returns the store containing the user's own aliases
+pub fn personal_store() -> FsStore {
+  return FsStore::new(PERSONAL_STORE, alias_path());
+}
+
+/// Returns the store containing aliases shared with others (the store named "shared"), if it has been configured
+pub fn shared_store() -> Result<Option<FsStore>, JumpcutError> {
+  return Ok(default_stores()?.into_iter().find(|store| store.name() == SHARED_STORE));
+}
+
+/// Returns the store containing the aliases of the project in the current directory, if any
+///
+/// This is the first PROJECT_FOLDER found in the current directory or any of its parents (like git looks for `.git`).
+/// The home directory is skipped, as that's where the personal aliases are stored.
+pub fn project_store() -> Option<FsStore> {
+  let home = dirs::home_dir();
+  let cwd = env::current_dir().ok()?;
+  let folder = cwd.ancestors()
+    .filter(|dir| Some(dir.to_path_buf()) != home)
+    .map(|dir| dir.join(PROJECT_FOLDER))
+    .find(|path| path.is_dir() && *path != alias_path())?;
+  return Some(FsStore::new(PROJECT_STORE, folder));
+}
+
+/// Returns the alias stores that Jumpcut uses, in order of precedence
+///
+/// These are the personal store, the stores in JUMPCUT_PATH (or if that isn't set, the stores in the configuration
+/// file), and the shared store in JUMPCUT_SHARED_PATH (if set). Stores with a higher priority come first; stores with
+/// the same priority keep this order. The project store (if any) always comes first.
+pub fn default_stores() -> Result<Vec<FsStore>, JumpcutError> {
+  let config = Config::load_default()?;
+  let mut stores = vec![personal_store()];
+  for store in config.stores {
+    if stores.iter().any(|x| x.name() == store.name) {
+      return Err(JumpcutError::InvalidArgument(format!("there are multiple alias stores named {:?}.", store.name)));
+    }
+    stores.push(FsStore::new(&store.name, store.path).read_only(store.read_only).priority(store.priority));
+  }
+  if let Some(path) = alias_shared_path() {
+    if !stores.iter().any(|x| x.name() == SHARED_STORE) {
+      stores.push(FsStore::new(SHARED_STORE, path));
+    }
+  }
+  stores.sort_by_key(|store| std::cmp::Reverse(store.priority));
+  if let Some(project) = project_store().filter(|project| stores.iter().all(|x| x.path() != project.path())) {
+    if stores.iter().any(|x| x.name() == PROJECT_STORE) {
+      return Err(JumpcutError::InvalidArgument(format!("there are multiple alias stores named {:?}.", PROJECT_STORE)));
+    }
+    stores.insert(0, project);
+  }
+  return Ok(stores);
+}
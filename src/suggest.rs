@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use crate::dir_import::{cd_command, propose_names, DirEntry};
+use crate::history::{literal_arguments, parameterize, suggest_param_name};
+
+/// Commands shorter than this aren't worth an alias
+pub const MIN_COMMAND_LENGTH: usize = 15;
+
+/// A proposed alias, for a command or directory that occurs repeatedly in the shell history
+#[derive(Clone, PartialEq, Debug)]
+pub struct Suggestion {
+  pub name: String,
+  pub command: String, // arguments that vary between uses are replaced by ?[param] placeholders
+  pub count: usize, // how often it was used
+  pub example: String // the most recent use
+}
+
+/// One command in the history, split up into its literal arguments and the rest
+struct Shape {
+  template: String, // the command without its arguments
+  values: Vec<String>
+}
+
+/// Finds commands and directories that are used at least `min_count` times in `history` (from oldest to newest)
+///
+/// Commands that only differ in their arguments are grouped, and the arguments that vary become placeholders.
+/// Anything that's already covered by one of the `existing` alias commands is left out, as are invocations of Jumpcut
+/// itself (commands whose first word is one of `jumpcut_names`, e.g. the name of the wrapper function). The proposed
+/// names avoid the names for which `is_taken` returns true. The suggestions are ranked by how much typing they'd save.
+pub fn suggest(history: &[String], existing: &[String], min_count: usize, jumpcut_names: &[String], is_taken: &dyn Fn(&str) -> bool) -> Vec<Suggestion> {
+  let mut groups: HashMap<String, Vec<(&str, Shape)>> = HashMap::new();
+  let mut dirs: HashMap<PathBuf, (usize, &str)> = HashMap::new();
+  for cmd in history.iter().map(|cmd| cmd.trim()) {
+    let first_word = cmd.split_whitespace().next().unwrap_or_default();
+    if jumpcut_names.iter().any(|name| Path::new(first_word).file_stem() == Some(name.as_ref())) {
+      continue;
+    }
+    if let Some(dir) = cd_target(cmd) {
+      let entry = dirs.entry(dir).or_insert((0, cmd));
+      *entry = (entry.0 + 1, cmd);
+    } else if cmd.len() >= MIN_COMMAND_LENGTH && first_word != "cd" {
+      let shape = shape(cmd);
+      groups.entry(shape.template.clone()).or_default().push((cmd, shape));
+    }
+  }
+
+  let mut candidates: Vec<(String, Suggestion)> = Vec::new(); // (proposed name, suggestion)
+  for uses in groups.values().filter(|uses| uses.len() >= min_count) {
+    let command = generalize(uses);
+    if !existing.iter().any(|alias| covers(alias, &command) || uses.iter().any(|(cmd, _)| covers(alias, cmd))) {
+      let (example, _) = uses[uses.len() - 1];
+      candidates.push((command_name(&command), Suggestion{name: String::new(), command: command, count: uses.len(), example: example.to_string()}));
+    }
+  }
+  let existing_dirs: Vec<PathBuf> = existing.iter().filter_map(|alias| cd_target(alias)).collect();
+  for (dir, (count, example)) in dirs.into_iter().filter(|(dir, (count, _))| *count >= min_count && !existing_dirs.contains(dir)) {
+    // (Directories whose path can't be quoted safely are left out)
+    let command = match dir.to_str().and_then(cd_command) {
+      Some(command) => command,
+      None => continue
+    };
+    let entry = DirEntry{path: dir, score: 0.0};
+    if let Some((name, _)) = propose_names(&[entry], is_taken).pop() {
+      candidates.push((name, Suggestion{name: String::new(), command: command, count: count, example: example.to_string()}));
+    }
+  }
+
+  // (Rank by the number of characters that would be saved, and then alphabetically, so the order is stable)
+  candidates.sort_by(|(_, a), (_, b)| (b.count * b.example.len()).cmp(&(a.count * a.example.len())).then(a.command.cmp(&b.command)));
+  let mut suggestions: Vec<Suggestion> = Vec::new();
+  for (name, mut suggestion) in candidates {
+    let available = |name: &String| !is_taken(name) && !suggestions.iter().any(|s| s.name == *name);
+    suggestion.name = (1..).map(|i| if i == 1 {name.clone()} else {format!("{}-{}", name, i)})
+      .find(available)
+      .unwrap_or_default();
+    suggestions.push(suggestion);
+  }
+  return suggestions;
+}
+
+/// Splits a command into its literal arguments, and a template of the rest of the command
+fn shape(cmd: &str) -> Shape {
+  let mut template = String::new();
+  let mut values = Vec::new();
+  let mut pos = 0;
+  for word in literal_arguments(cmd) {
+    template.push_str(&cmd[pos..word.start]);
+    template.push('\0');
+    values.push(cmd[word.start..word.end].to_string());
+    pos = word.end;
+  }
+  template.push_str(&cmd[pos..]);
+  return Shape{template: template, values: values};
+}
+
+/// Turns a group of commands with the same shape into a single command, in which the arguments that vary are parameters
+fn generalize(uses: &[(&str, Shape)]) -> String {
+  let (latest, _) = uses[uses.len() - 1];
+  let arguments = literal_arguments(latest);
+  let mut names = Vec::new();
+  let mut replacements = Vec::new();
+  for (i, argument) in arguments.into_iter().enumerate() {
+    let (_, first) = &uses[0];
+    if uses.iter().any(|(_, shape)| shape.values[i] != first.values[i]) {
+      let name = suggest_param_name(&argument.text, &names);
+      names.push(name.clone());
+      replacements.push((argument, name));
+    }
+  }
+  return parameterize(latest, &replacements);
+}
+
+/// Is `cmd` an instance of the alias command `alias` (in which placeholders can stand for anything)?
+fn covers(alias: &str, cmd: &str) -> bool {
+  let placeholder = Regex::new(r"\?(?:\[[^\]]*\]|[0-9]+|[@*]|pwd)").unwrap();
+  let mut pattern = String::from("^");
+  let mut pos = 0;
+  for m in placeholder.find_iter(alias) {
+    pattern.push_str(&regex::escape(&alias[pos..m.start()]));
+    pattern.push_str(".*");
+    pos = m.end();
+  }
+  pattern.push_str(&regex::escape(&alias[pos..]));
+  pattern.push('$');
+  return Regex::new(&pattern).map(|re| re.is_match(cmd.trim())).unwrap_or(false);
+}
+
+/// If `cmd` navigates to an absolute path (or a path in the home directory) that exists, returns that path
+fn cd_target(cmd: &str) -> Option<PathBuf> {
+  let re = Regex::new(r#"^cd\s+(?:"([^"]+)"|'([^']+)'|([^\s;&|"']+))\s*$"#).unwrap();
+  let caps = re.captures(cmd.trim())?;
+  let path = caps.get(1).or_else(|| caps.get(2)).or_else(|| caps.get(3))?.as_str();
+  let path = match path.strip_prefix("~/") {
+    Some(rest) => dirs::home_dir()?.join(rest),
+    None => PathBuf::from(path)
+  };
+  if !path.is_absolute() || !path.is_dir() {
+    return None;
+  }
+  // (Remove any trailing slashes or `..`, so each directory is only counted once)
+  return path.canonicalize().ok().filter(|path| path != Path::new("/"));
+}
+
+/// Proposes a name for a command, from the first letters of its first words (e.g. "kgp" for `kubectl get pods -A`)
+fn command_name(command: &str) -> String {
+  let initials: String = command.split_whitespace()
+    .take_while(|word| !["|", ";", "&&", "||"].contains(word))
+    .filter(|word| !word.starts_with('-') && !word.starts_with('?'))
+    .filter_map(|word| word.chars().find(|c| c.is_ascii_alphanumeric()))
+    .take(3)
+    .collect();
+  return if initials.is_empty() {"cmd".to_string()} else {initials.to_lowercase()};
+}
@@ -0,0 +1,194 @@
+use std::path::Path;
+use std::process::Command;
+use crate::error::JumpcutError;
+use crate::store::{AliasStore, FsStore};
+
+/// Hash of git's empty tree, to compare against if the repository doesn't have any commits yet
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// How an alias was changed
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ChangeKind {
+  Added,
+  Changed,
+  Removed
+}
+
+/// An alias that was changed, either locally or by someone else
+#[derive(Clone, PartialEq, Debug)]
+pub struct Change {
+  pub kind: ChangeKind,
+  pub alias: String
+}
+
+/// Which version of an alias to keep, if it was changed both locally and by someone else
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ConflictChoice {
+  /// Keep the local version
+  Ours,
+  /// Take the other version
+  Theirs
+}
+
+/// What `sync` did
+#[derive(Clone, Default, Debug)]
+pub struct SyncReport {
+  /// Local changes that were committed
+  pub committed: Vec<Change>,
+  /// Changes made by others that were pulled in
+  pub pulled: Vec<Change>,
+  /// Whether any local commits were pushed
+  pub pushed: bool
+}
+
+/// Synchronizes an alias store whose folder is (part of) a git working copy with its remote
+///
+/// Any local changes to the store's aliases are committed (with a generated message), the remote's changes are merged
+/// in, and the result is pushed. If an alias was changed both locally and remotely, `conflict_handler` decides which
+/// version to keep; if it fails, the merge is aborted, and nothing is pushed.
+///
+/// A read-only store is only brought up to date with the remote: nothing is committed or pushed, and syncing fails
+/// if the remote changes can't be fast-forwarded (e.g. because the working copy has local commits).
+pub fn sync(store: &FsStore, conflict_handler: &dyn Fn(&str) -> Result<ConflictChoice, JumpcutError>) -> Result<SyncReport, JumpcutError> {
+  let dir = store.path();
+  if !dir.is_dir() || git(dir, &["rev-parse", "--is-inside-work-tree"]).is_err() {
+    return Err(JumpcutError::Sync(format!("{} is not a git working copy.", dir.display())));
+  }
+  let mut report = SyncReport::default();
+  let read_only = store.is_read_only();
+
+  if !read_only {
+    git(dir, &["add", "--all", "--", "."])?;
+    report.committed = staged_changes(dir)?;
+    if !report.committed.is_empty() {
+      git(dir, &["commit", "--quiet", "--message", &commit_message(&report.committed), "--", "."])?;
+    }
+  }
+
+  let branch = git(dir, &["symbolic-ref", "--short", "HEAD"])?;
+  let remote = git(dir, &["config", &format!("branch.{}.remote", branch)]).unwrap_or_else(|_| "origin".to_string());
+  git(dir, &["fetch", "--quiet", &remote])?;
+  let has_upstream = git(dir, &["rev-parse", "--verify", "--quiet", "@{upstream}"]).is_ok();
+  let upstream = if has_upstream {
+    Some("@{upstream}".to_string())
+  } else {
+    // (E.g. in a fresh clone of an empty repository)
+    let remote_branch = format!("{}/{}", remote, branch);
+    git(dir, &["rev-parse", "--verify", "--quiet", &remote_branch]).ok().map(|_| remote_branch)
+  };
+
+  if let Some(upstream) = upstream {
+    let before = git(dir, &["rev-parse", "--verify", "--quiet", "HEAD"]).unwrap_or_else(|_| EMPTY_TREE.to_string());
+    if read_only {
+      if git(dir, &["merge", "--quiet", "--ff-only", &upstream]).is_err() {
+        return Err(JumpcutError::Sync(format!(
+          "the {:?} alias store is read-only, so its local changes can't be merged with the remote changes.", store.name())));
+      }
+    } else if git(dir, &["merge", "--quiet", "--no-edit", &upstream]).is_err() {
+      resolve_conflicts(dir, conflict_handler)?;
+    }
+    report.pulled = changes(dir, &["diff", "--name-status", "-z", "--relative", &before, "HEAD", "--", "."])?;
+    if read_only {
+      return Ok(report);
+    }
+    let ahead = git(dir, &["rev-list", "--count", &format!("{}..HEAD", upstream)])?;
+    if ahead != "0" || !has_upstream {
+      git(dir, &["push", "--quiet", "--set-upstream", &remote, &branch])?;
+      report.pushed = true;
+    }
+  } else if !read_only && git(dir, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_ok() {
+    git(dir, &["push", "--quiet", "--set-upstream", &remote, &branch])?;
+    report.pushed = true;
+  }
+  return Ok(report);
+}
+
+/// Resolves a failed merge, by letting `conflict_handler` choose a version of each conflicting alias
+fn resolve_conflicts(dir: &Path, conflict_handler: &dyn Fn(&str) -> Result<ConflictChoice, JumpcutError>) -> Result<(), JumpcutError> {
+  let conflicts: Vec<String> = git(dir, &["diff", "--name-only", "-z", "--relative", "--diff-filter=U", "--", "."])?
+    .split('\0').filter(|alias| !alias.is_empty()).map(|alias| alias.to_string()).collect();
+  if conflicts.is_empty() {
+    git(dir, &["merge", "--abort"]).ok();
+    return Err(JumpcutError::Sync(format!("could not merge the remote changes into {}.", dir.display())));
+  }
+
+  for alias in conflicts.iter() {
+    let choice = match conflict_handler(alias) {
+      Ok(choice) => choice,
+      Err(e) => {
+        git(dir, &["merge", "--abort"])?;
+        return Err(match e {
+          JumpcutError::InputRequired(_) => JumpcutError::Sync(format!(
+            "these aliases were changed both locally and remotely: {}. Run `j sync` interactively to choose which versions to keep.",
+            conflicts.join(", "))),
+          e => e
+        });
+      }
+    };
+    let side = if choice == ConflictChoice::Ours {"--ours"} else {"--theirs"};
+    if git(dir, &["checkout", side, "--", alias]).is_ok() {
+      git(dir, &["add", "--", alias])?;
+    } else {
+      // (The chosen version is the one that removed the alias)
+      git(dir, &["rm", "--quiet", "--", alias])?;
+    }
+  }
+  git(dir, &["commit", "--quiet", "--no-edit"])?;
+  return Ok(());
+}
+
+/// Returns the changes that are staged to be committed
+fn staged_changes(dir: &Path) -> Result<Vec<Change>, JumpcutError> {
+  return changes(dir, &["diff", "--cached", "--name-status", "-z", "--relative", "--", "."]);
+}
+
+/// Parses the output of `git diff --name-status -z` (ignoring hidden files, which aren't aliases)
+///
+/// With `-z`, the status and file names are separated by NUL characters, and file names aren't quoted or escaped.
+fn changes(dir: &Path, diff_args: &[&str]) -> Result<Vec<Change>, JumpcutError> {
+  let mut changes = Vec::new();
+  let output = git(dir, diff_args)?;
+  let mut fields = output.split('\0').filter(|field| !field.is_empty());
+  while let Some(status) = fields.next() {
+    let kind = match status.chars().next() {
+      Some('A') => ChangeKind::Added,
+      Some('D') => ChangeKind::Removed,
+      _ => ChangeKind::Changed
+    };
+    // (Renamed and copied files have two names, of which the second is the alias's current name)
+    let name = if status.starts_with('R') || status.starts_with('C') {fields.nth(1)} else {fields.next()};
+    if let Some(alias) = name.filter(|alias| !alias.starts_with('.')) {
+      changes.push(Change{kind: kind, alias: alias.to_string()});
+    }
+  }
+  return Ok(changes);
+}
+
+/// Generates a commit message describing the given changes, e.g. "Add deploy, build; remove test"
+fn commit_message(changes: &[Change]) -> String {
+  let mut parts = Vec::new();
+  for (kind, verb) in [(ChangeKind::Added, "add"), (ChangeKind::Changed, "update"), (ChangeKind::Removed, "remove")] {
+    let aliases: Vec<&str> = changes.iter().filter(|c| c.kind == kind).map(|c| c.alias.as_str()).collect();
+    if !aliases.is_empty() {
+      parts.push(format!("{} {}", verb, aliases.join(", ")));
+    }
+  }
+  let message = parts.join("; ");
+  let mut chars = message.chars();
+  let capitalized = match chars.next() {
+    Some(first) => first.to_uppercase().chain(chars).collect(),
+    None => "Update aliases".to_string()
+  };
+  return format!("{}\n\nCommitted by `j sync`", capitalized);
+}
+
+/// Runs a git command in `dir`, and returns its output (without any trailing whitespace)
+fn git(dir: &Path, args: &[&str]) -> Result<String, JumpcutError> {
+  let output = Command::new("git").current_dir(dir).args(args).output()
+    .map_err(|e| JumpcutError::Sync(format!("could not run git: {}", e)))?;
+  if !output.status.success() {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    return Err(JumpcutError::Sync(format!("`git {}` failed: {}", args.join(" "), stderr.trim())));
+  }
+  return Ok(String::from_utf8_lossy(&output.stdout).trim_end().to_string());
+}
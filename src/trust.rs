@@ -0,0 +1,104 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::error::JumpcutError;
+use crate::store::canonical_alias_file;
+
+/// A version of an alias that the user approved
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct TrustedAlias {
+  pub hash: String, // SHA-256 hash of the alias file's contents (see `content_hash`)
+  pub contents: String // the alias file's contents, to show what changed since
+}
+
+/// How an alias compares to the version the user last trusted
+#[derive(Clone, PartialEq, Debug)]
+pub enum TrustStatus {
+  /// The alias is identical to the trusted version
+  Trusted,
+  /// The alias was never trusted before
+  New,
+  /// The alias changed since it was trusted (the trusted version's contents)
+  Changed(String)
+}
+
+/// A line in the difference between two versions of an alias file
+#[derive(Clone, PartialEq, Debug)]
+pub enum DiffLine {
+  Same(String),
+  Removed(String),
+  Added(String)
+}
+
+/// Which versions of the aliases in non-personal stores (e.g. shared or project aliases) the user trusts
+///
+/// Aliases are identified by the path of their alias file, so each store is kept separately.
+#[derive(Serialize, Deserialize, Default)]
+pub struct TrustDb {
+  #[serde(default)]
+  aliases: BTreeMap<String, TrustedAlias>
+}
+
+impl TrustDb {
+  /// Reads the trust database; if it doesn't exist (yet), nothing is trusted
+  pub fn load(path: &Path) -> Result<TrustDb, JumpcutError> {
+    if !path.exists() {
+      return Ok(TrustDb::default());
+    }
+    let contents = fs::read_to_string(path)?;
+    return toml::from_str(&contents).map_err(|e| JumpcutError::InvalidFile(path.to_path_buf(), e.to_string()));
+  }
+
+  /// Writes the trust database to file
+  pub fn save(&self, path: &Path) -> Result<(), JumpcutError> {
+    let data = toml::to_string(self).map_err(|e| JumpcutError::InvalidFile(path.to_path_buf(), e.to_string()))?;
+    fs::write(path, data)?;
+    return Ok(());
+  }
+
+  /// Checks whether the alias stored in `alias_file`, which currently contains `contents`, is trusted
+  pub fn status(&self, alias_file: &Path, contents: &str) -> TrustStatus {
+    return match self.aliases.get(&key(alias_file)) {
+      None => TrustStatus::New,
+      Some(trusted) if trusted.hash == content_hash(contents) => TrustStatus::Trusted,
+      Some(trusted) => TrustStatus::Changed(trusted.contents.clone())
+    };
+  }
+
+  /// Trust the alias stored in `alias_file`, as long as it contains `contents`
+  pub fn trust(&mut self, alias_file: &Path, contents: &str) {
+    self.aliases.insert(key(alias_file), TrustedAlias{hash: content_hash(contents), contents: contents.to_string()});
+  }
+
+  /// Keep trusting an alias that was moved/renamed from `old_file` to `new_file`
+  pub fn rename(&mut self, old_file: &Path, new_file: &Path) {
+    if let Some(trusted) = self.aliases.remove(&key(old_file)) {
+      self.aliases.insert(key(new_file), trusted);
+    }
+  }
+
+  /// Forget about the alias stored in `alias_file`
+  pub fn remove(&mut self, alias_file: &Path) {
+    self.aliases.remove(&key(alias_file));
+  }
+}
+
+fn key(alias_file: &Path) -> String {
+  return canonical_alias_file(alias_file).to_string_lossy().to_string();
+}
+
+/// Returns the SHA-256 hash of an alias file's contents, as a hexadecimal string
+pub fn content_hash(contents: &str) -> String {
+  return Sha256::digest(contents.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect();
+}
+
+/// Compares two versions of an alias file, line by line
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+  return diff::lines(old, new).into_iter().map(|line| match line {
+    diff::Result::Both(line, _) => DiffLine::Same(line.to_string()),
+    diff::Result::Left(line) => DiffLine::Removed(line.to_string()),
+    diff::Result::Right(line) => DiffLine::Added(line.to_string())
+  }).collect();
+}
@@ -0,0 +1,268 @@
+use pancurses::{Input, Window, A_BOLD, A_REVERSE};
+use jumpcut::alias::Alias;
+use jumpcut::error::JumpcutError;
+use jumpcut::browse::{confirmation_name, filter_aliases, preview_lines, scroll_position};
+use jumpcut::resolve::Candidate;
+use jumpcut::stats::UsageStats;
+use jumpcut::store::{AliasStore, FsStore, shared_store};
+use jumpcut::paths::*;
+use crate::{alias_label, is_trusted, put_modified_alias, remember_trusted, update_stats, update_trust};
+
+const HELP: &str = "Enter: execute  ^E: edit  ^S: copy to shared  ^O: confirmation  ^D: delete  Esc: quit";
+
+// Control characters, as returned by getch() in raw mode
+const KEY_CTRL_C: char = '\u{3}';
+const KEY_CTRL_D: char = '\u{4}';
+const KEY_CTRL_E: char = '\u{5}';
+const KEY_CTRL_O: char = '\u{f}';
+const KEY_CTRL_S: char = '\u{13}';
+const KEY_ESC: char = '\u{1b}';
+
+/// State of the full-screen alias browser
+struct Browser<'a> {
+  stores: &'a [FsStore],
+  stats: UsageStats,
+  filter: String,
+  matches: Vec<Candidate>, // all aliases that match the filter, best matches first
+  selected: usize, // index in `matches`
+  scroll: usize, // index in `matches` of the first alias that is shown
+  status: String // message shown above the help line
+}
+
+/// Opens a full-screen browser listing all aliases in `stores`
+///
+/// Typing filters the list (using the same matching as when executing an alias), and the preview pane shows the
+/// details of the selected alias. Aliases can also be edited, copied to the shared store and deleted.
+/// Returns the alias that the user chose to execute (its store index and name), if any.
+pub fn browse(stores: &[FsStore]) -> Result<Option<(usize, String)>, JumpcutError> {
+  let mut browser = Browser {
+    stores: stores,
+    stats: UsageStats::load(&stats_path()).unwrap_or_default(),
+    filter: String::new(),
+    matches: Vec::new(),
+    selected: 0,
+    scroll: 0,
+    status: String::new()
+  };
+  browser.update_matches()?;
+
+  let window = pancurses::initscr();
+  window.keypad(true);
+  pancurses::raw();
+  pancurses::noecho();
+  pancurses::curs_set(0);
+  let result = browser.run(&window);
+  pancurses::endwin();
+  return result;
+}
+
+impl<'a> Browser<'a> {
+  /// Handles key presses until the user executes an alias or quits
+  fn run(&mut self, window: &Window) -> Result<Option<(usize, String)>, JumpcutError> {
+    loop {
+      self.draw(window);
+      let result = match window.getch() {
+        Some(Input::Character(KEY_ESC)) | Some(Input::Character(KEY_CTRL_C)) => return Ok(None),
+        Some(Input::Character('\n')) | Some(Input::Character('\r')) | Some(Input::KeyEnter) => {
+          if let Some(c) = self.matches.get(self.selected) {
+            return Ok(Some((c.store, c.name.clone())));
+          }
+          Ok(())
+        },
+        Some(Input::KeyUp) => self.move_selection(-1),
+        Some(Input::KeyDown) => self.move_selection(1),
+        Some(Input::KeyPPage) => self.move_selection(-page_size(window)),
+        Some(Input::KeyNPage) => self.move_selection(page_size(window)),
+        Some(Input::KeyBackspace) | Some(Input::Character('\u{7f}')) | Some(Input::Character('\u{8}')) => {
+          self.filter.pop();
+          self.update_matches()
+        },
+        Some(Input::Character(KEY_CTRL_E)) => self.edit(window),
+        Some(Input::Character(KEY_CTRL_S)) => self.share(),
+        Some(Input::Character(KEY_CTRL_O)) => self.cycle_confirmation(),
+        Some(Input::Character(KEY_CTRL_D)) => self.delete(window),
+        Some(Input::Character(c)) if !c.is_control() => {
+          self.filter.push(c);
+          self.update_matches()
+        },
+        _ => Ok(())
+      };
+      // Errors shouldn't close the browser; just show them
+      if let Err(e) = result {
+        self.status = format!("Error: {}", e);
+      }
+    }
+  }
+
+  /// Recompute which aliases match the filter
+  fn update_matches(&mut self) -> Result<(), JumpcutError> {
+    self.matches = filter_aliases(self.stores, &self.filter, &self.stats)?;
+    self.selected = 0;
+    self.scroll = 0;
+    return Ok(());
+  }
+
+  fn move_selection(&mut self, delta: i32) -> Result<(), JumpcutError> {
+    let last = self.matches.len().saturating_sub(1) as i32;
+    self.selected = (self.selected as i32 + delta).max(0).min(last) as usize;
+    return Ok(());
+  }
+
+  /// Returns the selected alias and its store
+  fn current(&self) -> Result<(&'a FsStore, Alias), JumpcutError> {
+    let c = self.matches.get(self.selected).ok_or_else(|| JumpcutError::NotFound(self.filter.clone()))?;
+    let store = &self.stores[c.store];
+    let al = store.get(&c.name)?.ok_or_else(|| JumpcutError::NotFound(c.name.clone()))?;
+    return Ok((store, al));
+  }
+
+  /// Edit the command of the selected alias
+  fn edit(&mut self, window: &Window) -> Result<(), JumpcutError> {
+    let (store, al) = self.current()?;
+    if al.get_command().contains('\n') {
+      self.status = format!("Multi-line commands can't be edited here; use `j add {} --file FILE` instead.", al.get_alias());
+      return Ok(());
+    }
+    if let Some(cmd) = prompt_line(window, "Command: ", al.get_command()) {
+      if cmd.trim().is_empty() {
+        self.status = "The command can't be empty; nothing was changed.".to_string();
+      } else {
+        let name = al.get_alias().to_string();
+        let updated = al.update_command(cmd.trim_end());
+        match updated.validate() {
+          Ok(()) => {
+            put_modified_alias(&updated, store)?;
+            self.status = format!("Updated {}", name);
+          },
+          Err(e) => self.status = format!("Invalid command: {}; nothing was changed.", e)
+        }
+      }
+    }
+    return Ok(());
+  }
+
+  /// Copy the selected alias to the shared store
+  fn share(&mut self) -> Result<(), JumpcutError> {
+    let (store, al) = self.current()?;
+    let shared = shared_store()?.ok_or_else(|| JumpcutError::InvalidArgument(
+      format!("no shared storage path configured; set the {} environment variable.", JUMPCUT_SHARED_ENV_VAR)))?;
+    let name = al.get_alias().to_string();
+    if store.path() == shared.path() {
+      self.status = format!("{} already is a shared alias.", name);
+    } else if shared.contains(&name) {
+      return Err(JumpcutError::AlreadyExists(format!("{} (shared)", name)));
+    } else {
+      shared.put(&al.update_storage_path(shared.alias_file(&name)))?;
+      if is_trusted(&name, store) {
+        remember_trusted(&name, &shared).ok();
+      }
+      self.update_matches()?;
+      self.status = format!("Copied {} to the shared store", name);
+    }
+    return Ok(());
+  }
+
+  /// Switch the selected alias to the next confirmation level
+  fn cycle_confirmation(&mut self) -> Result<(), JumpcutError> {
+    let (store, al) = self.current()?;
+    let level = (al.get_confirmation_level() + 1) % 3;
+    let name = al.get_alias().to_string();
+    put_modified_alias(&al.update_confirm(level), store)?;
+    self.status = format!("Confirmation of {}: {}", name, confirmation_name(level));
+    return Ok(());
+  }
+
+  /// Delete the selected alias, after asking for confirmation
+  fn delete(&mut self, window: &Window) -> Result<(), JumpcutError> {
+    let (store, al) = self.current()?;
+    let name = al.get_alias().to_string();
+    let answer = prompt_line(window, &format!("Delete {}? (y/n) ", alias_label(&name, store)), "");
+    if answer.map(|x| x.trim().to_lowercase()) == Some("y".to_string()) {
+      store.remove(&name)?;
+      update_stats(|stats| stats.remove(&store.alias_file(&name))).ok();
+      update_trust(|db| db.remove(&store.alias_file(&name))).ok();
+      self.update_matches()?;
+      self.status = format!("Deleted {}", name);
+    }
+    return Ok(());
+  }
+
+  fn draw(&mut self, window: &Window) {
+    window.erase();
+    let (height, width) = window.get_max_yx();
+    let page = page_size(window) as usize;
+    self.scroll = scroll_position(self.selected, self.scroll, page);
+
+    window.attron(A_BOLD);
+    window.mvaddstr(0, 0, "Filter: ");
+    window.attroff(A_BOLD);
+    window.addstr(fit(&self.filter, width - 8));
+
+    let list_width = (width / 3).max(20).min(width);
+    if self.matches.is_empty() {
+      window.mvaddstr(2, 0, fit("No matching aliases", list_width));
+    }
+    for (row, c) in self.matches.iter().enumerate().skip(self.scroll).take(page) {
+      if row == self.selected {
+        window.attron(A_REVERSE);
+      }
+      let label = alias_label(&c.name, &self.stores[c.store]);
+      window.mvaddstr(2 + (row - self.scroll) as i32, 0, fit(&format!("{: <w$}", label, w=(list_width as usize).saturating_sub(1)), list_width - 1));
+      window.attroff(A_REVERSE);
+    }
+
+    if let Ok((store, al)) = self.current() {
+      let x = list_width + 1;
+      for (row, line) in preview_lines(&al, store.name()).iter().take(page).enumerate() {
+        window.mvaddstr(2 + row as i32, x, fit(line, width.saturating_sub(x)));
+      }
+    }
+
+    window.mvaddstr(height - 2, 0, fit(&self.status, width));
+    window.attron(A_BOLD);
+    window.mvaddstr(height - 1, 0, fit(HELP, width));
+    window.attroff(A_BOLD);
+    window.refresh();
+  }
+}
+
+/// Number of aliases that fit on the screen
+fn page_size(window: &Window) -> i32 {
+  return (window.get_max_y() - 4).max(1);
+}
+
+/// Cuts off `text` so it fits in `width` columns
+fn fit(text: &str, width: i32) -> String {
+  return text.chars().take(width.max(0) as usize).collect();
+}
+
+/// Lets the user enter a line of text at the bottom of the screen, starting from `initial`
+///
+/// Returns None if the user pressed Esc.
+fn prompt_line(window: &Window, prompt: &str, initial: &str) -> Option<String> {
+  let mut text = initial.to_string();
+  let y = window.get_max_y() - 2;
+  let width = window.get_max_x();
+  pancurses::curs_set(1);
+  let result = loop {
+    // (Only the end of the text is shown if it doesn't fit)
+    let visible = (width - prompt.chars().count() as i32 - 1).max(0) as usize;
+    let len = text.chars().count();
+    let shown: String = text.chars().skip(len.saturating_sub(visible)).collect();
+    window.mv(y, 0);
+    window.clrtoeol();
+    window.mvaddstr(y, 0, fit(&format!("{}{}", prompt, shown), width));
+    window.refresh();
+    match window.getch() {
+      Some(Input::Character('\n')) | Some(Input::Character('\r')) | Some(Input::KeyEnter) => break Some(text),
+      Some(Input::Character(KEY_ESC)) | Some(Input::Character(KEY_CTRL_C)) => break None,
+      Some(Input::KeyBackspace) | Some(Input::Character('\u{7f}')) | Some(Input::Character('\u{8}')) => {
+        text.pop();
+      },
+      Some(Input::Character(c)) if !c.is_control() => text.push(c),
+      _ => {}
+    }
+  };
+  pancurses::curs_set(0);
+  return result;
+}
@@ -1,3 +1,6 @@
+#![allow(clippy::needless_return, clippy::redundant_field_names, clippy::needless_borrows_for_generic_args,
+    clippy::empty_line_after_doc_comments)]
+
 /// Jumpcut integration tests
 /// 
 /// Tests must be run sequentially! (They rely on the contents of the alias file directory.)
@@ -6,6 +9,14 @@
 extern crate assert_cmd;
 
 use assert_cmd::prelude::*;
+use assert_cmd::stdin::CommandStdInExt;
+use std::process::Command;
+use jumpcut::{resolve, Alias, AliasStore, FsStore, JumpcutError, Resolution, ResolveOptions};
+use jumpcut::stats::UsageStats;
+use jumpcut::browse::{filter_aliases, preview_lines, scroll_position};
+use jumpcut::sync::{sync, Change, ChangeKind, ConflictChoice};
+use jumpcut::history::{literal_arguments, parameterize, suggest_param_name};
+use jumpcut::shell::Shell;
 mod utils;
 use utils::*;
 
@@ -53,4 +64,1041 @@ fn exec_alias_params() {
         let out = jc_cmd().args(&[alias, "---", "foo", "bar"]).output();
         assert_eq!(out_to_str(out), "mv foo bar\n");
     });
-}
\ No newline at end of file
+}
+
+#[test]
+fn add_alias_structured_format() {
+    run_test(|| {
+        let alias = "up";
+        jc_cmd().args(["add", alias, "cd .."]).unwrap();
+        jc_cmd().args(["desc", alias, "Go up"]).unwrap();
+
+        let contents = read_alias_file(alias);
+        assert!(contents.contains("version = 1"));
+        assert!(contents.contains("command = \"cd ..\""));
+        assert!(contents.contains("description = \"Go up\""));
+    });
+}
+
+#[test]
+fn exec_legacy_alias() {
+    run_test(|| {
+        let alias = "up";
+        write_alias_file(alias, "cd ..\nGo up\nconfirm=0");
+
+        let out = jc_cmd().args([alias]).output();
+        assert_eq!(out_to_str(out), "cd ..\n");
+
+        // A structured alias file without a version isn't mistaken for a legacy one
+        write_alias_file("broken", "command = \"ls\"\ndescription = \"List\"\nconfirm = 0\n");
+        jc_cmd().args(["broken"]).assert().code(6);
+        write_alias_file("broken", "ls\nList\nconfirm=0\nls -la\n");
+        jc_cmd().args(["broken"]).assert().code(6);
+    });
+}
+
+#[test]
+fn migrate_legacy_aliases() {
+    run_test(|| {
+        write_alias_file("up", "cd ..\nGo up\nconfirm=2");
+        write_alias_file("home", "cd ~");
+
+        let out = jc_cmd().args(["migrate"]).output();
+        let out_str = out_to_str(out);
+        assert!(out_str.starts_with("Migrated 2 alias(es)"));
+
+        let contents = read_alias_file("up");
+        assert!(contents.contains("version = 1"));
+        assert!(contents.contains("description = \"Go up\""));
+        assert!(contents.contains("confirm = 2"));
+
+        // Clean up the backup folder
+        let backup = out_str.trim_end().rsplit("(backup: ").next().unwrap().trim_end_matches(')');
+        assert!(std::path::Path::new(backup).join("up").exists());
+        std::fs::remove_dir_all(backup).unwrap();
+    });
+}
+
+#[test]
+fn add_multiline_alias_from_stdin() {
+    run_test(|| {
+        let alias = "greet";
+        jc_cmd().args(["add", alias, "-"]).with_stdin().buffer("echo hello\necho world\n").unwrap();
+        assert!(alias_exists(alias));
+
+        let out = jc_cmd().args([alias]).output();
+        assert_eq!(out_to_str(out), "echo hello\necho world\n");
+
+        let out = jc_cmd().args(["list"]).output();
+        let out_str = out_to_str(out);
+        assert!(out_str.contains("echo hello ... (+1 more lines)"));
+        assert!(!out_str.contains("echo world"));
+    });
+}
+
+#[test]
+fn exec_alias_all_positional_params() {
+    run_test(|| {
+        let alias = "greet";
+        jc_cmd().args(["add", alias, "echo ?[greeting] ?@"]).unwrap();
+
+        let out = jc_cmd().args([alias, "--greeting=hello", "--", "foo", "bar"]).output();
+        assert_eq!(out_to_str(out), "echo hello foo bar\n");
+    });
+}
+
+#[test]
+fn exec_alias_param_defaults_and_choices() {
+    run_test(|| {
+        let alias = "deploy";
+        jc_cmd().args(["add", alias, "deploy --env ?[env|dev,staging,prod=staging] --port ?[port:int=8080]"]).unwrap();
+
+        let out = jc_cmd().args([alias]).output();
+        assert_eq!(out_to_str(out), "deploy --env staging --port 8080\n");
+
+        let out = jc_cmd().args([alias, "--env=prod", "--port=443"]).output();
+        assert_eq!(out_to_str(out), "deploy --env prod --port 443\n");
+
+        // Defaults must match the parameter's type and choices
+        jc_cmd().args(["add", "bad-int", "serve --port ?[port:int=http]"]).assert().failure();
+        jc_cmd().args(["add", "bad-choice", "deploy --env ?[env|dev,prod=staging]"]).assert().failure();
+    });
+}
+
+#[test]
+fn exec_alias_quotes_param_values() {
+    run_test(|| {
+        let alias = "greet";
+        jc_cmd().args(["add", alias, "echo ?[msg] ?@ ?[!raw]"]).unwrap();
+
+        let out = jc_cmd().env("JUMPCUT_SHELL", "bash")
+            .args([alias, "--msg=hi; rm -rf /", "--raw=$HOME", "--", "it's", "plain"]).output();
+        assert_eq!(out_to_str(out), "echo 'hi; rm -rf /' 'it'\\''s' plain $HOME\n");
+
+        let out = jc_cmd().env("JUMPCUT_SHELL", "powershell")
+            .args([alias, "--msg=$(whoami)", "--raw=x", "--", "it's"]).output();
+        assert_eq!(out_to_str(out), "echo '$(whoami)' 'it''s' x\n");
+
+        // Without any arguments, ?* expands to nothing (rather than to an empty argument)
+        jc_cmd().args(["add", "commit", "git commit ?*"]).unwrap();
+        let out = jc_cmd().env("JUMPCUT_SHELL", "bash").args(["commit", "--", "fix", "it"]).output();
+        assert_eq!(out_to_str(out), "git commit 'fix it'\n");
+        let out = jc_cmd().env("JUMPCUT_SHELL", "bash").args(["commit"]).output();
+        assert_eq!(out_to_str(out), "git commit \n");
+    });
+}
+
+#[test]
+fn quote_for_each_shell() {
+    assert_eq!(Shell::Bash.quote("it's"), "'it'\\''s'");
+    assert_eq!(Shell::Bash.quote("--opt=ls"), "--opt=ls");
+    // (zsh would expand `=cmd` to the path of a command)
+    assert_eq!(Shell::Zsh.quote("=python3"), "'=python3'");
+    assert_eq!(Shell::Zsh.quote("--opt==ls"), "'--opt==ls'");
+    assert_eq!(Shell::Posix.quote("=ls"), "'=ls'");
+    assert_eq!(Shell::Nushell.quote("plain"), "plain");
+    assert_eq!(Shell::Nushell.quote("a b"), "'a b'");
+    assert_eq!(Shell::Nushell.quote("it's"), "r#'it's'#");
+    // (The raw string must not end early at a quote followed by #'s)
+    assert_eq!(Shell::Nushell.quote("x'# ; rm -rf ~ ; r#'"), "r##'x'# ; rm -rf ~ ; r#''##");
+    assert_eq!(Shell::Nushell.quote("'## and '#"), "r###''## and '#'###");
+}
+
+#[test]
+fn init_bash_wrapper() {
+    run_test(|| {
+        let wrapper = out_to_str(jc_cmd().args(["init", "bash", "--name", "jc"]).output());
+        let script = format!("{}\njc add up cd ..\njc add greet echo ?1\njc greet -- \"hi there\"\njc up\npwd", wrapper);
+
+        let out = Command::new("bash").env("TMPDIR", scratch_path()).args(["-c", &script]).output();
+        let parent = std::env::current_dir().unwrap().parent().unwrap().display().to_string();
+        assert_eq!(out_to_str(out), format!("hi there\n{}\n", parent));
+        // (The wrapper's temporary folders are removed again)
+        assert_eq!(std::fs::read_dir(scratch_path()).unwrap().count(), 0);
+    });
+}
+
+#[test]
+fn init_other_shells() {
+    run_test(|| {
+        for (shell, expected) in [("zsh", "x() {"), ("fish", "function x"), ("powershell", "function x {"), ("nushell", "def --env --wrapped x")] {
+            let wrapper = out_to_str(jc_cmd().args(["init", shell, "--name=x"]).output());
+            assert!(wrapper.contains(expected));
+            assert!(wrapper.contains("JUMPCUT_CMD_FILE"));
+        }
+        // (PowerShell's environment variables last for the whole session, so the wrapper restores them)
+        let wrapper = out_to_str(jc_cmd().args(["init", "powershell"]).output());
+        assert!(wrapper.contains("finally {") && wrapper.contains("$global:LASTEXITCODE = $jumpcut_status"));
+
+        let out = jc_cmd().args(["init", "tcsh"]).output();
+        assert_eq!(out_to_str(out), "");
+    });
+}
+
+#[test]
+fn exec_alias_cmd_file_protocol() {
+    run_test(|| {
+        let alias = "up";
+        jc_cmd().args(["add", alias, "cd .."]).unwrap();
+        let cmd_file = alias_path().join("..").join(".jumpcut_test_cmd");
+
+        let out = jc_cmd().env("JUMPCUT_CMD_FILE", &cmd_file).args([alias]).output().unwrap();
+        assert_eq!(out.status.code(), Some(10));
+        assert!(out.stdout.is_empty());
+        assert_eq!(std::fs::read_to_string(&cmd_file).unwrap(), "cd ..");
+        std::fs::remove_file(&cmd_file).unwrap();
+
+        let out = jc_cmd().env("JUMPCUT_CMD_FILE", &cmd_file).args(["list"]).output().unwrap();
+        assert_eq!(out.status.code(), Some(0));
+        assert!(!cmd_file.exists());
+
+        // An existing command file is never overwritten
+        std::fs::write(&cmd_file, "echo planted").unwrap();
+        jc_cmd().env("JUMPCUT_CMD_FILE", &cmd_file).args([alias]).assert().code(7);
+        assert_eq!(std::fs::read_to_string(&cmd_file).unwrap(), "echo planted");
+        std::fs::remove_file(&cmd_file).unwrap();
+    });
+}
+
+#[test]
+fn complete_keywords_aliases_and_params() {
+    run_test(|| {
+        jc_cmd().args(["add", "deploy", "deploy ?[env|dev,prod] ?[port:int] ?1"]).unwrap();
+        jc_cmd().args(["add", "devbox", "ssh dev"]).unwrap();
+
+        let out = jc_cmd().args(["__complete", "de"]).output();
+        assert_eq!(out_to_str(out), "desc\ndemote\ndeploy\ndevbox\n");
+
+        let out = jc_cmd().args(["__complete", "rm", "box"]).output();
+        assert_eq!(out_to_str(out), "devbox\n");
+
+        let out = jc_cmd().args(["__complete", "depl", "--"]).output();
+        assert_eq!(out_to_str(out), "--env=\n--port=\n");
+
+        let out = jc_cmd().args(["__complete", "completions", "p"]).output();
+        assert_eq!(out_to_str(out), "powershell\n");
+    });
+}
+
+#[test]
+fn completions_script() {
+    run_test(|| {
+        let out = out_to_str(jc_cmd().args(["completions", "bash", "--name", "jc"]).output());
+        assert!(out.contains("complete -F _jumpcut_complete_jc jc"));
+
+        let out = out_to_str(jc_cmd().args(["completions", "fish"]).output());
+        assert!(out.starts_with("complete -c j "));
+    });
+}
+
+#[test]
+fn exec_alias_fuzzy_match() {
+    run_test(|| {
+        jc_cmd().args(["add", "git-push", "git push"]).unwrap();
+        jc_cmd().args(["add", "git-pull", "git pull"]).unwrap();
+        jc_cmd().args(["add", "rebuild-all", "make clean all"]).unwrap();
+
+        let out = jc_cmd().args(["GPS"]).output();
+        assert_eq!(out_to_str(out), "git push\n");
+
+        let out = jc_cmd().args(["gpl"]).output();
+        assert_eq!(out_to_str(out), "git pull\n");
+
+        let out = jc_cmd().args(["rbl"]).output();
+        assert_eq!(out_to_str(out), "make clean all\n");
+
+        // Among several candidates, a much better match wins ..
+        jc_cmd().args(["add", "print-queue-status-batch", "lpq -a"]).unwrap();
+        let out = jc_cmd().args(["--no-input", "push"]).output();
+        assert_eq!(out_to_str(out), "git push\n");
+
+        // .. but if none stands out, we can't pick one without asking
+        jc_cmd().args(["add", "docker-ps", "docker ps"]).unwrap();
+        jc_cmd().args(["add", "deploy-staging", "deploy staging"]).unwrap();
+        let out = jc_cmd().args(["--no-input", "dps"]).output().unwrap();
+        assert_eq!(out.status.code(), Some(3));
+        assert!(String::from_utf8_lossy(&out.stderr).contains("docker-ps, deploy-staging"));
+    });
+}
+
+#[test]
+fn frecency_stats_and_auto_pick() {
+    run_test(|| {
+        jc_cmd().args(["add", "gpush", "git push"]).unwrap();
+        jc_cmd().args(["add", "gpull", "git pull"]).unwrap();
+        jc_cmd().args(["gpush"]).unwrap();
+        jc_cmd().args(["gpush"]).unwrap();
+
+        let out = jc_cmd().env("JUMPCUT_AUTO_PICK", "1").args(["gp"]).output();
+        assert_eq!(out_to_str(out), "git push\n");
+
+        let out = out_to_str(jc_cmd().args(["stats"]).output());
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[0].contains("gpush") && lines[0].contains("3x"));
+        assert!(lines[1].contains("gpull") && lines[1].contains("never used"));
+
+        // The same alias is counted once, however the path to its store is written
+        let shared_via_dot = shared_path().parent().unwrap().join(".").join(".jumpcut_test_shared");
+        jc_cmd().env("JUMPCUT_SHARED_PATH", shared_path()).args(["addshr", "deploy", "echo deploy"]).unwrap();
+        jc_cmd().env("JUMPCUT_SHARED_PATH", shared_path()).args(["deploy"]).unwrap();
+        jc_cmd().env("JUMPCUT_SHARED_PATH", &shared_via_dot).args(["deploy"]).unwrap();
+        let out = out_to_str(jc_cmd().env("JUMPCUT_SHARED_PATH", shared_path()).args(["stats"]).output());
+        assert!(out.lines().any(|line| line.contains("deploy") && line.contains("2x")));
+    });
+}
+
+#[test]
+fn library_store_and_resolve() {
+    run_test(|| {
+        let store = FsStore::new("test", alias_path());
+        store.put(&Alias::new("git-push", "git push", "", 0, store.alias_file("git-push"))).unwrap();
+        store.put(&Alias::new("git-pull", "git pull", "", 0, store.alias_file("git-pull"))).unwrap();
+        assert_eq!(store.list().unwrap(), vec!["git-pull", "git-push"]);
+        assert_eq!(store.get("git-push").unwrap().unwrap().get_command(), "git push");
+
+        let stores = [store];
+        let stats = UsageStats::default();
+        let query = |q: &[&str]| {
+            let q: Vec<String> = q.iter().map(|x| x.to_string()).collect();
+            return resolve(&stores, &q, &stats, ResolveOptions::default()).unwrap();
+        };
+        match query(&["gps"]) {
+            Resolution::Unique(c) => assert_eq!(c.name, "git-push"),
+            other => panic!("unexpected resolution: {:?}", other)
+        }
+        match query(&["git"]) {
+            Resolution::Ambiguous(cs) => assert_eq!(cs.len(), 2),
+            other => panic!("unexpected resolution: {:?}", other)
+        }
+        assert_eq!(query(&["xyz"]), Resolution::NotFound);
+
+        stores[0].rename("git-push", "gp").unwrap();
+        assert!(stores[0].rename("gp", "git-pull").is_err());
+        stores[0].remove("gp").unwrap();
+        assert_eq!(stores[0].list().unwrap(), vec!["git-pull"]);
+        assert!(!alias_exists("gp"));
+
+        // Alias names can't point outside of the store's folder
+        let escaped = home_path().join("escaped");
+        assert!(stores[0].put(&Alias::new("../escaped", "rm -rf ~", "", 0, stores[0].alias_file("../escaped"))).is_err());
+        assert!(stores[0].rename("git-pull", "../escaped").is_err());
+        jc_cmd().args(["add", "../escaped", "rm -rf ~"]).assert().code(1);
+        assert!(!escaped.exists());
+    });
+}
+
+#[test]
+fn browse_filter_and_preview() {
+    run_test(|| {
+        let store = FsStore::new("personal", alias_path());
+        store.put(&Alias::new("git-push", "git push", "Push to origin", 1, store.alias_file("git-push"))).unwrap();
+        store.put(&Alias::new("git-pull", "git pull", "", 0, store.alias_file("git-pull"))).unwrap();
+        store.put(&Alias::new("deploy", "deploy --env ?[env|dev,prod]\necho done", "", 2, store.alias_file("deploy"))).unwrap();
+        let stores = [store];
+        let stats = UsageStats::default();
+
+        let names = |filter: &str| -> Vec<String> {
+            return filter_aliases(&stores, filter, &stats).unwrap().into_iter().map(|c| c.name).collect();
+        };
+        assert_eq!(names("").len(), 3);
+        assert_eq!(names("gps"), vec!["git-push"]);
+        assert_eq!(names("git pl"), vec!["git-pull"]);
+        assert!(names("xyz").is_empty());
+
+        let preview = preview_lines(&stores[0].get("git-push").unwrap().unwrap(), "personal");
+        assert_eq!(preview, vec!["Command:", "  git push", "", "Description:  Push to origin", "Confirmation: y/n confirmation",
+            "Store:        personal", "Parameters:   none"]);
+        let preview = preview_lines(&stores[0].get("deploy").unwrap().unwrap(), "personal");
+        assert_eq!(&preview[..3], ["Command:", "  deploy --env ?[env|dev,prod]", "  echo done"]);
+        assert!(preview.contains(&"Confirmation: explicit confirmation".to_string()));
+        assert_eq!(preview.last().unwrap(), "  ?[env] (one of: dev, prod)");
+
+        // The list only scrolls when the selection would move out of view
+        assert_eq!(scroll_position(3, 0, 5), 0);
+        assert_eq!(scroll_position(7, 0, 5), 3);
+        assert_eq!(scroll_position(2, 3, 5), 2);
+        assert_eq!(scroll_position(4, 3, 0), 4);
+    });
+}
+
+#[test]
+fn exit_codes() {
+    run_test(|| {
+        jc_cmd().args(["add", "gpush", "git push"]).unwrap();
+        jc_cmd().args(["add", "gpull", "git pull"]).unwrap();
+
+        jc_cmd().args(["nonexistent"]).assert().code(2);
+        jc_cmd().args(["rm", "nonexistent"]).assert().code(2);
+        jc_cmd().args(["add", "list", "ls"]).assert().code(4);
+        jc_cmd().args(["add", "x"]).assert().code(1);
+        jc_cmd().args(["addpath", "x", "/nonexistent/path"]).assert().code(1);
+        jc_cmd().args(["init", "tcsh"]).assert().code(1);
+
+        write_alias_file("broken", "version = 99\ncommand = \"ls\"\n");
+        jc_cmd().args(["broken"]).assert().code(6);
+
+        jc_cmd().args(["confirm", "gpush", "yes"]).assert().code(1);
+        jc_cmd().args(["confirm", "gpush", "3"]).assert().code(1);
+        write_alias_file("odd", "version = 1\ncommand = \"ls\"\nconfirm = 3\n");
+        jc_cmd().args(["odd"]).assert().code(1);
+    });
+}
+
+#[test]
+fn non_interactive_mode() {
+    run_test(|| {
+        jc_cmd().args(["add", "gpush", "git push"]).unwrap();
+        jc_cmd().args(["add", "gpull", "git pull"]).unwrap();
+        jc_cmd().args(["add", "greet", "echo ?[name]"]).unwrap();
+        jc_cmd().args(["add", "nuke", "rm -rf build"]).unwrap();
+        jc_cmd().args(["confirm", "nuke", "1"]).unwrap();
+
+        let out = jc_cmd().args(["--no-input", "gp"]).output().unwrap();
+        assert_eq!(out.status.code(), Some(3));
+        assert!(String::from_utf8_lossy(&out.stderr).contains("gpull, gpush"));
+
+        jc_cmd().env("JUMPCUT_NONINTERACTIVE", "1").args(["greet"]).assert().code(8);
+        jc_cmd().args(["--no-input", "greet", "--name=bob"]).assert().success();
+        jc_cmd().args(["--no-input", "nuke"]).assert().code(8);
+        jc_cmd().args(["--no-input", "add", "gpush", "git push --force"]).assert().code(5);
+        assert!(read_alias_file("gpush").contains("command = \"git push\""));
+        jc_cmd().args(["ui"]).assert().code(8);
+    });
+}
+
+#[test]
+fn edit_alias_in_editor() {
+    run_test(|| {
+        jc_cmd().args(["add", "gpush", "git push"]).unwrap();
+
+        jc_cmd().env("EDITOR", "sed -i -e s/^command.*/command=\"git\\x20push\\x20--force\"/ -e s/^confirm.*/confirm=1/").env_remove("VISUAL")
+            .args(["edit", "gpush"]).unwrap();
+        let contents = read_alias_file("gpush");
+        assert!(contents.contains("command = \"git push --force\""));
+        assert!(contents.contains("confirm = 1"));
+
+        jc_cmd().env("EDITOR", "sed -i s/^name.*/name=\"gpf\"/").env_remove("VISUAL").args(["edit", "gpush"]).unwrap();
+        assert!(!alias_exists("gpush"));
+        assert!(read_alias_file("gpf").contains("git push --force"));
+
+        // Invalid changes are rejected (the edited file is kept in TMPDIR)
+        for script in ["s/^confirm.*/confirm=7/", "s/^name.*/name=\"list\"/", "s/push/?[x:float]/", "s/^version.*//"] {
+            jc_cmd().env("EDITOR", format!("sed -i {}", script)).env_remove("VISUAL").env("TMPDIR", scratch_path())
+                .args(["edit", "gpf"]).assert().code(6);
+            assert!(read_alias_file("gpf").contains("command = \"git push --force\""));
+        }
+        assert_eq!(std::fs::read_dir(scratch_path()).unwrap().count(), 4);
+        assert_eq!(std::fs::read_dir(alias_path()).unwrap().count(), 1);
+    });
+}
+
+#[test]
+fn move_promote_and_demote_aliases() {
+    run_test(|| {
+        jc_cmd().args(["add", "gpush", "git push"]).unwrap();
+        jc_cmd().args(["desc", "gpush", "Push changes"]).unwrap();
+        jc_cmd().args(["confirm", "gpush", "1"]).unwrap();
+        jc_cmd().args(["add", "gpull", "git pull"]).unwrap();
+
+        jc_cmd().args(["mv", "gpush", "gp"]).unwrap();
+        assert!(!alias_exists("gpush"));
+        let contents = read_alias_file("gp");
+        assert!(contents.contains("description = \"Push changes\"") && contents.contains("confirm = 1"));
+
+        jc_cmd().args(["mv", "gp", "gpull"]).assert().code(5);
+        jc_cmd().args(["mv", "gp", "list"]).assert().code(4);
+        jc_cmd().args(["promote", "gp"]).assert().code(1);
+
+        jc_cmd().env("JUMPCUT_SHARED_PATH", shared_path()).args(["promote", "gp"]).unwrap();
+        assert!(!alias_exists("gp"));
+        assert_eq!(std::fs::read_to_string(shared_path().join("gp")).unwrap(), contents);
+
+        jc_cmd().args(["add", "gp", "echo personal"]).unwrap();
+        jc_cmd().env("JUMPCUT_SHARED_PATH", shared_path()).args(["demote", "gp"]).assert().code(5);
+        // (The personal alias shadows the shared one)
+        jc_cmd().env("JUMPCUT_SHARED_PATH", shared_path()).args(["rm", "gp"]).unwrap();
+        assert!(!alias_exists("gp"));
+        jc_cmd().env("JUMPCUT_SHARED_PATH", shared_path()).args(["demote", "gp"]).unwrap();
+        assert_eq!(read_alias_file("gp"), contents);
+        assert!(!shared_path().join("gp").exists());
+    });
+}
+
+#[test]
+fn layered_alias_stores() {
+    run_test(|| {
+        let team_path = shared_path().join("team");
+        std::fs::create_dir_all(&team_path).unwrap();
+        std::fs::write(team_path.join("deploy"), "echo team\nDeploy\nconfirm=0").unwrap();
+        std::fs::write(team_path.join("build"), "echo build\nBuild\nconfirm=0").unwrap();
+        jc_cmd().args(["add", "deploy", "echo personal"]).unwrap();
+
+        let config = format!("[[store]]\nname = \"team\"\npath = {:?}\nread_only = true\npriority = 10\n", team_path);
+        std::fs::write(config_path(), &config).unwrap();
+        jc_cmd().args(["trust", "deploy"]).unwrap();
+        jc_cmd().args(["trust", "build"]).unwrap();
+        assert_eq!(out_to_str(jc_cmd().args(["deploy"]).output()), "echo team\n");
+        assert_eq!(out_to_str(jc_cmd().args(["bui"]).output()), "echo build\n");
+        jc_cmd().args(["rm", "build"]).assert().code(9);
+        jc_cmd().args(["desc", "deploy", "Deploy it"]).assert().code(9);
+
+        let out = out_to_str(jc_cmd().args(["list"]).output());
+        let team_header = out.find("Aliases in team folder").unwrap();
+        let personal_header = out.find("Aliases in personal folder").unwrap();
+        assert!(team_header < personal_header);
+        assert!(out.contains("[read-only]"));
+        assert!(out.contains("[shadowed by deploy (team)]"));
+
+        // Name conflicts can also be resolved by asking the user
+        std::fs::write(config_path(), format!("on_conflict = \"ask\"\n{}", config)).unwrap();
+        jc_cmd().args(["deploy"]).assert().code(3);
+        jc_cmd().args(["rm", "deploy"]).assert().code(3);
+
+        // JUMPCUT_PATH replaces the stores in the configuration file
+        std::fs::remove_file(config_path()).unwrap();
+        let jumpcut_path = format!("team={}", team_path.display());
+        assert_eq!(out_to_str(jc_cmd().env("JUMPCUT_PATH", &jumpcut_path).args(["deploy"]).output()), "echo personal\n");
+        jc_cmd().env("JUMPCUT_PATH", &jumpcut_path).args(["rm", "build"]).unwrap();
+        assert!(!team_path.join("build").exists());
+        jc_cmd().env("JUMPCUT_PATH", format!("team,ro={}", team_path.display())).args(["rm", "deploy"]).unwrap();
+        assert!(!alias_exists("deploy"));
+        jc_cmd().env("JUMPCUT_PATH", format!("team,ro={}", team_path.display())).args(["rm", "deploy"]).assert().code(9);
+
+        std::fs::write(config_path(), "on_conflict = \"sometimes\"").unwrap();
+        jc_cmd().args(["list"]).assert().code(6);
+    });
+}
+
+#[test]
+fn project_aliases() {
+    run_test(|| {
+        let project_path = shared_path().join("project");
+        let src_path = project_path.join("src");
+        std::fs::create_dir_all(&src_path).unwrap();
+        jc_cmd().current_dir(&project_path).args(["add", "--project", "build", "cargo build"]).unwrap();
+        assert!(project_path.join(".jumpcut").join("build").is_file());
+        jc_cmd().current_dir(&src_path).args(["add", "--project", "test", "cargo test"]).unwrap();
+        assert!(project_path.join(".jumpcut").join("test").is_file());
+        jc_cmd().current_dir(&src_path).args(["add", "build", "make"]).unwrap();
+
+        // Project aliases are found from any subfolder, and take precedence over personal aliases
+        assert_eq!(out_to_str(jc_cmd().current_dir(&src_path).args(["build"]).output()), "cargo build\n");
+        assert_eq!(out_to_str(jc_cmd().current_dir(&src_path).args(["tes"]).output()), "cargo test\n");
+        let out = out_to_str(jc_cmd().current_dir(&src_path).args(["list"]).output());
+        assert!(out.starts_with("Aliases in project folder:"));
+        assert!(out.contains("Aliases in personal folder:"));
+
+        // Outside of the project, its aliases aren't available
+        jc_cmd().args(["tes"]).assert().code(2);
+    });
+}
+
+#[test]
+fn trust_project_aliases() {
+    run_test(|| {
+        let project_path = shared_path().join("project");
+        std::fs::create_dir_all(project_path.join(".jumpcut")).unwrap();
+        std::fs::write(project_path.join(".jumpcut").join("build"), "cargo build\nBuild\nconfirm=0").unwrap();
+
+        // Project aliases that the user didn't write must be approved first
+        jc_cmd().current_dir(&project_path).args(["build"]).assert().code(8);
+        jc_cmd().current_dir(&project_path).args(["trust", "build"]).unwrap();
+        assert_eq!(out_to_str(jc_cmd().current_dir(&project_path).args(["build"]).output()), "cargo build\n");
+
+        // .. but the ones the user added are trusted
+        jc_cmd().current_dir(&project_path).args(["add", "--project", "test", "cargo test"]).unwrap();
+        assert_eq!(out_to_str(jc_cmd().current_dir(&project_path).args(["test"]).output()), "cargo test\n");
+    });
+}
+
+#[test]
+fn trust_shared_aliases() {
+    run_test(|| {
+        std::fs::create_dir_all(shared_path()).unwrap();
+        std::fs::write(shared_path().join("deploy"), "echo deploy\nDeploy\nconfirm=0").unwrap();
+        let shared_cmd = || {
+            let mut cmd = jc_cmd();
+            cmd.env("JUMPCUT_SHARED_PATH", shared_path());
+            cmd
+        };
+
+        // Aliases written by someone else must be approved first
+        let out = shared_cmd().args(["deploy"]).output().unwrap();
+        assert_eq!(out.status.code(), Some(8));
+        assert!(String::from_utf8_lossy(&out.stderr).contains("is new"));
+        shared_cmd().args(["trust", "deploy"]).unwrap();
+        assert_eq!(out_to_str(shared_cmd().args(["deploy"]).output()), "echo deploy\n");
+
+        // .. and approved again whenever they change
+        std::fs::write(shared_path().join("deploy"), "rm -rf /\nDeploy\nconfirm=0").unwrap();
+        let out = shared_cmd().args(["deploy"]).output().unwrap();
+        assert_eq!(out.status.code(), Some(8));
+        assert!(String::from_utf8_lossy(&out.stderr).contains("has changed since you last trusted it"));
+
+        // Aliases that the user wrote are trusted
+        shared_cmd().args(["addshr", "hello", "echo hello"]).unwrap();
+        assert_eq!(out_to_str(shared_cmd().args(["hello"]).output()), "echo hello\n");
+        shared_cmd().args(["desc", "hello", "Says hello"]).unwrap();
+        assert_eq!(out_to_str(shared_cmd().args(["hello"]).output()), "echo hello\n");
+        jc_cmd().args(["add", "up", "cd .."]).unwrap();
+        shared_cmd().args(["promote", "up"]).unwrap();
+        assert_eq!(out_to_str(shared_cmd().args(["up"]).output()), "cd ..\n");
+
+        // .. but modifying an untrusted alias doesn't make it trusted
+        shared_cmd().args(["desc", "deploy", "Deploy it"]).unwrap();
+        shared_cmd().args(["deploy"]).assert().code(8);
+
+        // .. and copying or demoting it to the personal folder (where everything is trusted) requires approving it too
+        shared_cmd().args(["cp", "deploy", "deploy2"]).assert().code(8);
+        shared_cmd().args(["demote", "deploy"]).assert().code(8);
+        assert!(!alias_path().join("deploy2").exists());
+        assert!(!alias_path().join("deploy").exists());
+        assert!(shared_path().join("deploy").exists());
+        shared_cmd().args(["trust", "deploy"]).unwrap();
+        shared_cmd().args(["cp", "deploy", "deploy2"]).unwrap();
+        shared_cmd().args(["demote", "deploy"]).unwrap();
+        assert!(alias_path().join("deploy").is_file());
+    });
+}
+
+#[test]
+fn sync_shared_aliases_with_git() {
+    run_test(|| {
+        let ours = shared_path().join("ours");
+        let theirs = shared_path().join("theirs");
+        std::fs::create_dir_all(shared_path()).unwrap();
+        let git = |dir: &std::path::Path, args: &[&str]| {
+            Command::new("git").current_dir(dir).args(args).output().unwrap();
+        };
+        git(&shared_path(), &["init", "--quiet", "--bare", "remote.git"]);
+        git(&shared_path(), &["clone", "--quiet", "remote.git", "ours"]);
+        git(&shared_path(), &["clone", "--quiet", "remote.git", "theirs"]);
+        let sync_cmd = |shared: &std::path::Path| {
+            let mut cmd = jc_cmd();
+            cmd.env("JUMPCUT_SHARED_PATH", shared)
+                .env("GIT_AUTHOR_NAME", "Test").env("GIT_AUTHOR_EMAIL", "test@example.com")
+                .env("GIT_COMMITTER_NAME", "Test").env("GIT_COMMITTER_EMAIL", "test@example.com");
+            cmd
+        };
+
+        sync_cmd(&ours).args(["addshr", "deploy", "echo deploy"]).unwrap();
+        sync_cmd(&ours).args(["addshr", "build", "echo build"]).unwrap();
+        let out = out_to_str(sync_cmd(&ours).args(["sync"]).output());
+        assert!(out.contains("Committed your changes:") && out.contains("deploy") && out.contains("Pushed"));
+
+        // Teammates get the new aliases, and see what changed
+        let out = out_to_str(sync_cmd(&theirs).args(["sync"]).output());
+        assert!(out.contains("Changes by others:") && out.contains("build") && out.contains("deploy"));
+        assert!(theirs.join("deploy").is_file());
+        let out = out_to_str(sync_cmd(&theirs).args(["sync"]).output());
+        assert!(out.contains("up to date"));
+
+        sync_cmd(&theirs).args(["rm", "build"]).unwrap();
+        sync_cmd(&theirs).args(["sync"]).unwrap();
+        let out = out_to_str(sync_cmd(&ours).args(["sync"]).output());
+        assert!(out.contains("Changes by others:") && out.contains("build"));
+        assert!(!ours.join("build").exists());
+
+        // Conflicting changes to the same alias can't be merged without asking
+        sync_cmd(&ours).args(["desc", "deploy", "Ours"]).unwrap();
+        sync_cmd(&ours).args(["sync"]).unwrap();
+        sync_cmd(&theirs).args(["desc", "deploy", "Theirs"]).unwrap();
+        let out = sync_cmd(&theirs).args(["sync"]).output().unwrap();
+        assert_eq!(out.status.code(), Some(11));
+        assert!(String::from_utf8_lossy(&out.stderr).contains("deploy"));
+        assert!(std::fs::read_to_string(theirs.join("deploy")).unwrap().contains("Theirs"));
+
+        jc_cmd().args(["sync"]).assert().code(1);
+        jc_cmd().env("JUMPCUT_SHARED_PATH", std::env::temp_dir()).args(["sync"]).assert().code(11);
+    });
+}
+
+#[test]
+fn sync_conflict_choices() {
+    run_test(|| {
+        let ours = shared_path().join("ours");
+        let theirs = shared_path().join("theirs");
+        std::fs::create_dir_all(shared_path()).unwrap();
+        let git = |dir: &std::path::Path, args: &[&str]| {
+            Command::new("git").current_dir(dir).args(args).output().unwrap();
+        };
+        git(&shared_path(), &["init", "--quiet", "--bare", "remote.git"]);
+        for clone in ["ours", "theirs"] {
+            git(&shared_path(), &["clone", "--quiet", "remote.git", clone]);
+            git(&shared_path().join(clone), &["config", "user.name", "Test"]);
+            git(&shared_path().join(clone), &["config", "user.email", "test@example.com"]);
+        }
+        let our_store = FsStore::new("shared", ours.clone());
+        let their_store = FsStore::new("shared", theirs.clone());
+        let write = |dir: &std::path::Path, alias: &str, cmd: &str| std::fs::write(dir.join(alias), cmd).unwrap();
+        let no_conflicts = |alias: &str| -> Result<ConflictChoice, JumpcutError> {
+            panic!("unexpected conflict in {}", alias);
+        };
+
+        for alias in ["deploy", "build", "lint", "test"] {
+            write(&ours, alias, &format!("echo {}", alias));
+        }
+        sync(&our_store, &no_conflicts).unwrap();
+        sync(&their_store, &no_conflicts).unwrap();
+
+        // Both sides modify deploy and build; one side removes lint and test, which the other side modifies
+        write(&ours, "deploy", "echo our deploy");
+        write(&ours, "build", "echo our build");
+        std::fs::remove_file(ours.join("lint")).unwrap();
+        std::fs::remove_file(ours.join("test")).unwrap();
+        sync(&our_store, &no_conflicts).unwrap();
+        write(&theirs, "deploy", "echo their deploy");
+        write(&theirs, "build", "echo their build");
+        write(&theirs, "lint", "echo their lint");
+        write(&theirs, "test", "echo their test");
+        // (Names with non-ASCII characters aren't quoted by git)
+        write(&theirs, "naïve build", "echo naive");
+        let choices = |alias: &str| -> Result<ConflictChoice, JumpcutError> {
+            return match alias {
+                "deploy" | "lint" => Ok(ConflictChoice::Ours),
+                "build" | "test" => Ok(ConflictChoice::Theirs),
+                _ => panic!("unexpected conflict in {}", alias)
+            };
+        };
+        let report = sync(&their_store, &choices).unwrap();
+        assert!(report.committed.contains(&Change{kind: ChangeKind::Added, alias: "naïve build".to_string()}));
+        assert!(report.pushed);
+        assert_eq!(std::fs::read_to_string(theirs.join("deploy")).unwrap(), "echo their deploy");
+        assert_eq!(std::fs::read_to_string(theirs.join("build")).unwrap(), "echo our build");
+        assert_eq!(std::fs::read_to_string(theirs.join("lint")).unwrap(), "echo their lint");
+        assert!(!theirs.join("test").exists());
+
+        // The other side gets the resolved versions
+        let report = sync(&our_store, &no_conflicts).unwrap();
+        assert!(report.pulled.contains(&Change{kind: ChangeKind::Added, alias: "naïve build".to_string()}));
+        assert!(report.pulled.contains(&Change{kind: ChangeKind::Added, alias: "lint".to_string()}));
+        assert_eq!(std::fs::read_to_string(ours.join("deploy")).unwrap(), "echo their deploy");
+        assert!(!ours.join("test").exists());
+
+        // Read-only stores are only updated, without committing or pushing local changes
+        let read_only = FsStore::new("shared", ours.clone()).read_only(true);
+        write(&theirs, "fmt", "cargo fmt");
+        sync(&their_store, &no_conflicts).unwrap();
+        write(&ours, "local", "echo local");
+        let report = sync(&read_only, &no_conflicts).unwrap();
+        assert!(report.committed.is_empty() && !report.pushed);
+        assert_eq!(report.pulled, vec![Change{kind: ChangeKind::Added, alias: "fmt".to_string()}]);
+        sync(&their_store, &no_conflicts).unwrap();
+        assert!(!theirs.join("local").exists());
+
+        // .. and can't be merged with local commits
+        git(&ours, &["add", "local"]);
+        git(&ours, &["commit", "--quiet", "--message", "Add local"]);
+        write(&theirs, "clippy", "cargo clippy");
+        sync(&their_store, &no_conflicts).unwrap();
+        assert!(matches!(sync(&read_only, &no_conflicts), Err(JumpcutError::Sync(_))));
+        assert!(!ours.join("clippy").exists());
+    });
+}
+
+#[test]
+fn export_and_import_bundles() {
+    run_test(|| {
+        jc_cmd().args(["add", "up", "cd .."]).unwrap();
+        jc_cmd().args(["desc", "up", "Go up"]).unwrap();
+        jc_cmd().args(["confirm", "up", "2"]).unwrap();
+        jc_cmd().args(["add", "gp", "git push"]).unwrap();
+        write_alias_file("home", "cd ~\nGo home\nconfirm=0");
+        let up_contents = read_alias_file("up");
+
+        let bundle = out_to_str(jc_cmd().args(["export"]).output());
+        assert!(bundle.contains("\"jumpcut_bundle\": 1") && bundle.contains("\"command\": \"cd ..\"") && bundle.contains("\"Go home\""));
+        let out = out_to_str(jc_cmd().args(["export", "g"]).output());
+        assert!(out.contains("\"gp\"") && !out.contains("\"up\""));
+
+        let bundle_file = shared_path().join("bundle.json");
+        std::fs::create_dir_all(shared_path()).unwrap();
+        std::fs::write(&bundle_file, &bundle).unwrap();
+        for alias in ["up", "gp", "home"] {
+            jc_cmd().args(["rm", alias]).unwrap();
+        }
+
+        let out = out_to_str(jc_cmd().args(["import", "--dry-run"]).arg(&bundle_file).output());
+        assert!(out.contains("Would add 3 alias(es)"));
+        assert!(!alias_exists("up"));
+        let out = out_to_str(jc_cmd().arg("import").arg(&bundle_file).output());
+        assert!(out.contains("Added 3 alias(es)"));
+        assert_eq!(read_alias_file("up"), up_contents);
+        let out = out_to_str(jc_cmd().arg("import").arg(&bundle_file).output());
+        assert!(out.contains("Skipped 3 alias(es)") && out.contains("up (unchanged)"));
+
+        // Conflicting aliases
+        jc_cmd().args(["desc", "up", "Changed"]).unwrap();
+        let out = out_to_str(jc_cmd().arg("import").arg(&bundle_file).output());
+        assert!(out.contains("up (an alias with that name already exists)"));
+        jc_cmd().args(["import", "--on-conflict", "ask"]).arg(&bundle_file).assert().code(8);
+        jc_cmd().args(["import", "--on-conflict=sometimes"]).arg(&bundle_file).assert().code(1);
+        jc_cmd().args(["import", "--on-conflict", "rename"]).arg(&bundle_file).unwrap();
+        assert_eq!(read_alias_file("up-2").replace("Go up", "Changed"), read_alias_file("up"));
+        let out = out_to_str(jc_cmd().args(["import", "--on-conflict", "overwrite"]).arg(&bundle_file).output());
+        assert!(out.contains("Changed 1 alias(es)"));
+        assert_eq!(read_alias_file("up"), up_contents);
+
+        jc_cmd().args(["import", "--prefix", "org1-"]).arg(&bundle_file).unwrap();
+        assert!(alias_exists("org1-up") && alias_exists("org1-gp") && alias_exists("org1-home"));
+
+        // Unknown fields are kept, and invalid aliases are skipped
+        std::fs::write(&bundle_file, r#"{"jumpcut_bundle": 1, "aliases": [
+            {"name": "tagged", "version": 1, "command": "ls", "tags": ["files"]},
+            {"name": "broken", "command": "ls"},
+            {"name": "list", "version": 1, "command": "ls"}
+        ]}"#).unwrap();
+        let out = out_to_str(jc_cmd().arg("import").arg(&bundle_file).output());
+        assert!(out.contains("Added 1 alias(es)") && out.contains("Skipped 2 alias(es)"));
+        assert!(read_alias_file("tagged").contains("tags = [\"files\"]"));
+        assert!(!alias_exists("broken") && !alias_exists("list"));
+
+        std::fs::write(&bundle_file, "{}").unwrap();
+        jc_cmd().arg("import").arg(&bundle_file).assert().code(6);
+    });
+}
+
+#[test]
+fn import_shell_aliases() {
+    run_test(|| {
+        std::fs::create_dir_all(shared_path()).unwrap();
+        let bashrc = shared_path().join("bashrc");
+        std::fs::write(&bashrc, r#"# Aliases
+alias ll='ls -la' gs="git status"
+alias cols='awk '\''{print $1}'\'''
+alias list='ls'
+alias -g G='| grep'
+mkcd() {
+    mkdir -p "$1"
+    cd "$1"
+}
+gcm() { git commit -m "$*"; }
+greet() { echo "Hello ${1:-world}"; }
+hello() { echo "Hello $1"; }
+src() { cd $1/src; }
+export PATH=$PATH:~/bin
+"#).unwrap();
+        let out = out_to_str(jc_cmd().args(["import-shell", "bash"]).arg(&bashrc).output());
+        assert!(out.contains("Imported 5 alias(es)"));
+        assert!(out.contains("list (line 4): it is a reserved keyword"));
+        assert!(out.contains("G (line 5): alias option -g is not supported"));
+        assert!(out.contains("greet (line 11): the function uses `${1:`, which can't be converted"));
+        assert!(out.contains("hello (line 12): the function uses `$1` within a longer word or string"));
+        assert!(out.contains("src (line 13): the function uses `$1` within a longer word or string"));
+        assert!(read_alias_file("ll").contains("command = \"ls -la\""));
+        assert!(read_alias_file("cols").contains("command = \"awk '{print $1}'\""));
+        assert!(read_alias_file("mkcd").contains("command = \"mkdir -p ?1\\ncd ?1\""));
+        assert!(read_alias_file("gcm").contains("command = \"git commit -m ?*\""));
+        assert!(!alias_exists("list") && !alias_exists("PATH"));
+
+        // Existing aliases are kept
+        let out = out_to_str(jc_cmd().args(["import-shell", "zsh"]).arg(&bashrc).output());
+        assert!(out.contains("Imported 0 alias(es)") && out.contains("ll (line 2): an alias with that name already exists"));
+
+        let config_fish = shared_path().join("config.fish");
+        std::fs::write(&config_fish, r#"abbr -a gco git checkout
+alias gp 'git push'
+function take --description 'Make and enter a folder'
+    mkdir -p $argv[1]
+    and cd $argv[1]
+end
+function last
+    echo $argv[-1]
+end
+"#).unwrap();
+        let out = out_to_str(jc_cmd().args(["import-shell", "fish"]).arg(&config_fish).output());
+        assert!(out.contains("Imported 3 alias(es)") && out.contains("last (line 7)"));
+        assert!(read_alias_file("gco").contains("command = \"git checkout\""));
+        assert!(read_alias_file("take").contains("command = \"mkdir -p ?1\\nand cd ?1\""));
+        assert!(read_alias_file("take").contains("description = \"Make and enter a folder\""));
+
+        let profile = shared_path().join("profile.ps1");
+        std::fs::write(&profile, "Set-Alias -Name g -Value git\nfunction touch { New-Item -ItemType File $args[0] }\n").unwrap();
+        jc_cmd().args(["import-shell", "powershell"]).arg(&profile).unwrap();
+        assert!(read_alias_file("g").contains("command = \"git\""));
+        assert!(read_alias_file("touch").contains("command = \"New-Item -ItemType File ?1\""));
+
+        jc_cmd().args(["import-shell", "nushell"]).arg(&profile).assert().code(1);
+    });
+}
+
+#[test]
+fn import_directory_databases() {
+    run_test(|| {
+        let dirs = ["work/api", "home/api", "My Docs", "docs"].map(|dir| shared_path().join(dir));
+        for dir in dirs.iter() {
+            std::fs::create_dir_all(dir).unwrap();
+        }
+        let dir = |i: usize| dirs[i].to_str().unwrap().to_string();
+        jc_cmd().args(["add", "docs", "ls"]).unwrap();
+
+        let z_file = shared_path().join("z");
+        std::fs::write(&z_file, format!("{}|50|1700000000\n{}|30|1700000000\n{}|10|1\n{}/gone|99|1\n{}|2|1\n",
+            dir(0), dir(1), dir(2), dir(3), dir(3))).unwrap();
+        let out = out_to_str(jc_cmd().args(["import-dirs", "z", "--min-score", "5", "--file"]).arg(&z_file).output());
+        assert!(out.contains("Added 3 alias(es)"));
+        assert!(read_alias_file("api").contains(&format!("command = \"cd \\\"{}\\\"\"", dir(0))));
+        assert!(read_alias_file("home-api").contains(&dir(1)) && read_alias_file("my-docs").contains(&dir(2)));
+
+        // Directories that already have an alias are skipped, and names of existing aliases are avoided
+        let out = out_to_str(jc_cmd().args(["import-dirs", "z", "--file"]).arg(&z_file).output());
+        assert!(out.contains("Added 1 alias(es)"));
+        assert!(read_alias_file("jumpcut_test_shared-docs").contains(&dir(3)));
+        let out = out_to_str(jc_cmd().args(["import-dirs", "fasd", "--file"]).arg(&z_file).output());
+        assert!(out.contains("No new directories to import"));
+
+        let autojump_file = shared_path().join("autojump.txt");
+        std::fs::write(&autojump_file, format!("12.5\t{}\n40.0\t{}\n", dirs[0].parent().unwrap().display(), shared_path().display())).unwrap();
+        let out = out_to_str(jc_cmd().args(["import-dirs", "autojump", "--top", "1", "--file"]).arg(&autojump_file).output());
+        assert!(out.contains("Added 1 alias(es)") && out.contains("jumpcut_test_shared"));
+        assert!(!alias_exists("work"));
+
+        // zoxide's database is a binary file
+        let work = dirs[0].parent().unwrap().to_str().unwrap().to_string();
+        let mut zoxide_db = 3u32.to_le_bytes().to_vec();
+        zoxide_db.extend(1u64.to_le_bytes());
+        zoxide_db.extend((work.len() as u64).to_le_bytes());
+        zoxide_db.extend(work.as_bytes());
+        zoxide_db.extend(4.5f64.to_le_bytes());
+        zoxide_db.extend(1700000000u64.to_le_bytes());
+        let zoxide_file = shared_path().join("db.zo");
+        std::fs::write(&zoxide_file, &zoxide_db).unwrap();
+        jc_cmd().args(["import-dirs", "zoxide", "--file"]).arg(&zoxide_file).unwrap();
+        assert!(read_alias_file("work").contains(&work));
+        std::fs::write(&zoxide_file, &zoxide_db[..20]).unwrap();
+        jc_cmd().args(["import-dirs", "zoxide", "--file"]).arg(&zoxide_file).assert().code(6);
+
+        // Paths that shells would expand are skipped
+        let unsafe_dirs = ["a$b", "say \"hi\"", "back\\slash"].map(|dir| shared_path().join(dir));
+        let mut z_data = String::new();
+        for dir in unsafe_dirs.iter() {
+            std::fs::create_dir_all(dir).unwrap();
+            z_data.push_str(&format!("{}|50|1700000000\n", dir.display()));
+        }
+        std::fs::write(&z_file, z_data).unwrap();
+        let out = out_to_str(jc_cmd().args(["import-dirs", "z", "--file"]).arg(&z_file).output());
+        assert!(out.contains("Skipped 3 director(ies)") && out.contains("No new directories to import"));
+
+        jc_cmd().args(["import-dirs", "zoxide", "--file"]).arg(shared_path().join("missing")).assert().code(1);
+        jc_cmd().args(["import-dirs", "j"]).assert().code(1);
+    });
+}
+
+#[test]
+fn add_alias_from_history() {
+    run_test(|| {
+        std::fs::create_dir_all(shared_path()).unwrap();
+        let history_file = shared_path().join("history");
+        let addlast = |shell: &str, args: &[&str]| {
+            let mut cmd = jc_cmd();
+            cmd.env("JUMPCUT_SHELL", shell).env("JUMPCUT_HISTFILE", &history_file).arg("addlast").args(args);
+            return cmd;
+        };
+
+        std::fs::write(&history_file, "#1700000000\nls -la\n#1700000001\ngit log --oneline -5\ngit log --oneline -5\nj addlast gl\n").unwrap();
+        addlast("bash", &["gl"]).unwrap();
+        assert!(read_alias_file("gl").contains("command = \"git log --oneline -5\""));
+        addlast("bash", &["two", "2"]).unwrap();
+        assert!(read_alias_file("two").contains("command = \"ls -la\\ngit log --oneline -5\""));
+        addlast("bash", &["gl"]).assert().code(5);
+        addlast("bash", &["list"]).assert().code(4);
+        addlast("bash", &["x", "many"]).assert().code(1);
+
+        // Commands that run in the background or end in a comment are kept intact
+        std::fs::write(&history_file, "npm run watch &\nls # list\n").unwrap();
+        addlast("bash", &["dev", "2"]).unwrap();
+        assert!(read_alias_file("dev").contains("command = \"npm run watch &\\nls # list\""));
+        let script = out_to_str(jc_cmd().env("JUMPCUT_SHELL", "bash").arg("dev").output());
+        assert!(Command::new("bash").args(["-n", "-c", &script]).status().unwrap().success());
+
+        // Only invocations of Jumpcut itself are skipped (by its wrapper's name, or the binary's path)
+        std::fs::write(&history_file, "git addlast\njc addlast ga\n/usr/local/bin/jumpcut addlast ga\n").unwrap();
+        addlast("bash", &["ga"]).env("JUMPCUT_NAME", "jc").unwrap();
+        assert!(read_alias_file("ga").contains("command = \"git addlast\""));
+
+        std::fs::write(&history_file, ": 1700000000:0;make test\n: 1700000001:0;for f in *.txt; do\\\n  echo $f\\\ndone\n").unwrap();
+        addlast("zsh", &["each"]).unwrap();
+        assert!(read_alias_file("each").contains("command = \"for f in *.txt; do\\n  echo $f\\ndone\""));
+
+        std::fs::write(&history_file, "- cmd: echo \\\\o/\n  when: 1700000000\n- cmd: cd /tmp\n  when: 1700000001\n  paths:\n    - /tmp\n").unwrap();
+        addlast("fish", &["yay", "2"]).unwrap();
+        assert!(read_alias_file("yay").contains("command = \"echo \\\\o/\\ncd /tmp\""));
+
+        std::fs::write(&history_file, "Get-ChildItem\nGet-Process |`\n  Sort-Object CPU\n").unwrap();
+        addlast("powershell", &["procs"]).unwrap();
+        assert!(read_alias_file("procs").contains("command = \"Get-Process |\\n  Sort-Object CPU\""));
+
+        jc_cmd().env("JUMPCUT_SHELL", "bash").env("JUMPCUT_HISTFILE", shared_path().join("missing")).args(["addlast", "x"]).assert().code(1);
+    });
+}
+
+#[test]
+fn parameterize_history_commands() {
+    let texts = |command: &str| literal_arguments(command).into_iter().map(|word| word.text).collect::<Vec<String>>();
+    assert_eq!(texts(r#"git commit -m "fix the \"build\"" --author='Jane Doe'"#), ["commit", "fix the \"build\""]);
+    assert_eq!(texts("cargo test 2>&1 | tee test.log"), ["test", "test.log"]);
+    assert_eq!(texts("make test > out.txt 2> err.txt"), ["test", "out.txt", "err.txt"]);
+    assert_eq!(texts(r#"RUST_LOG=debug scp "$HOME/app.tar" deploy@$HOST:/srv ~/backup *.log"#), Vec::<String>::new());
+    assert_eq!(texts("echo 'no $expansion' \"$VAR\" && ls -la docs"), ["no $expansion", "docs"]);
+
+    let command = r#"grep -rn "TODO: fix" src 2>&1 | tee "$LOG""#;
+    let words = literal_arguments(command);
+    let mut names = Vec::new();
+    let mut replacements = Vec::new();
+    for word in words {
+        let name = suggest_param_name(&word.text, &names);
+        names.push(name.clone());
+        replacements.push((word, name));
+    }
+    assert_eq!(names, ["arg", "dir"]);
+    assert_eq!(parameterize(command, &replacements), r#"grep -rn ?[arg] ?[dir] 2>&1 | tee "$LOG""#);
+
+    assert_eq!(suggest_param_name("42", &[]), "n");
+    assert_eq!(suggest_param_name("https://example.com", &[]), "url");
+    assert_eq!(suggest_param_name("notes.txt", &["file".to_string()]), "file2");
+}
+
+#[test]
+fn suggest_aliases_from_history() {
+    run_test(|| {
+        let project = shared_path().join("proj");
+        std::fs::create_dir_all(&project).unwrap();
+        let unsafe_dir = shared_path().join("$(rm -rf ~)");
+        std::fs::create_dir_all(&unsafe_dir).unwrap();
+        let history_file = shared_path().join("history");
+        let mut history = String::new();
+        for env in ["prod", "staging", "prod"] {
+            history.push_str(&format!("kubectl get pods -n {}\ngit log --oneline --graph\nmake test\nj up\njc deploy --env {}\n", env, env));
+            history.push_str(&format!("docker compose up -d --build\ncd {}/\ncd '{}'\n", project.display(), unsafe_dir.display()));
+        }
+        std::fs::write(&history_file, history).unwrap();
+        jc_cmd().args(["add", "gl", "git log ?@"]).unwrap();
+        // (The wrapper function is called `jc` rather than `j`)
+        let suggest_cmd = || {
+            let mut cmd = jc_cmd();
+            cmd.env("JUMPCUT_SHELL", "bash").env("JUMPCUT_HISTFILE", &history_file).env("JUMPCUT_NAME", "jc");
+            return cmd;
+        };
+
+        let out = out_to_str(suggest_cmd().arg("suggest").output());
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1], format!("  proj  cd \"{}\"  (used 3 times)", project.display()));
+        assert_eq!(lines[2], "  dcu   docker compose up -d --build  (used 3 times)");
+        assert_eq!(lines[3], "  kgp   kubectl get pods -n ?[arg]  (used 3 times)");
+        assert!(!out.contains("git log") && !out.contains("make test") && !out.contains("rm -rf"));
+
+        // Commands and folders that already have an alias aren't suggested
+        jc_cmd().args(["addpath", "p"]).arg(&project).unwrap();
+        jc_cmd().args(["add", "pods", "kubectl get pods -n ?[ns]"]).unwrap();
+        let out = out_to_str(suggest_cmd().args(["suggest", "--top", "5"]).output());
+        assert_eq!(out.lines().count(), 2);
+
+        let out = out_to_str(suggest_cmd().args(["suggest", "--min-count=4"]).output());
+        assert!(out.starts_with("No suggestions"));
+
+        // Jumpcut's own invocations are only recognized by the wrapper's name
+        let out = out_to_str(suggest_cmd().env_remove("JUMPCUT_NAME").arg("suggest").output());
+        assert!(out.contains("jc deploy --env ?[arg]"));
+    });
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::needless_return)]
+
 use assert_cmd::prelude::*;
 use std::process::Command;
 use std::path::PathBuf;
@@ -6,6 +8,10 @@ use std::env;
 use std::io;
 use std::panic;
 use std::process::Output;
+use std::sync::Mutex;
+
+/// Tests share the same alias directory, so they must not run concurrently
+static TEST_LOCK: Mutex<()> = Mutex::new(());
 
 /// Return a Jumpcut Command process
 pub fn jc_cmd() -> Command {
@@ -15,9 +21,28 @@ pub fn jc_cmd() -> Command {
 /// Runs a Jumpcut test with setup/teardown phases
 /// 
 /// Based on: https://medium.com/@ericdreichert/test-setup-and-teardown-in-rust-without-a-framework-ba32d97aa5ab
-pub fn run_test<T>(test: T) -> () where T: FnOnce() -> () + panic::UnwindSafe {
+pub fn run_test<T>(test: T) where T: FnOnce() + panic::UnwindSafe {
     // Setup
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    env::set_var("JUMPCUT_HOME", home_path());
+    fs::create_dir_all(alias_path()).unwrap();
     remove_all_aliases().unwrap();
+    if stats_path().exists() {
+        fs::remove_file(stats_path()).unwrap();
+    }
+    if shared_path().exists() {
+        fs::remove_dir_all(shared_path()).unwrap();
+    }
+    if trust_path().exists() {
+        fs::remove_file(trust_path()).unwrap();
+    }
+    if config_path().exists() {
+        fs::remove_file(config_path()).unwrap();
+    }
+    if scratch_path().exists() {
+        fs::remove_dir_all(scratch_path()).unwrap();
+    }
+    fs::create_dir_all(scratch_path()).unwrap();
     
     // Test body
     let result = panic::catch_unwind(|| {
@@ -28,18 +53,60 @@ pub fn run_test<T>(test: T) -> () where T: FnOnce() -> () + panic::UnwindSafe {
     assert!(result.is_ok())
 }
 
-/// Copy of alias_path() in src/utils.rs
-fn alias_path() -> PathBuf {
+/// Folder that the tests use instead of the home directory (passed via JUMPCUT_HOME)
+pub fn home_path() -> PathBuf {
     let pwd = env::current_dir().unwrap_or_default();
     return pwd.join(".jumpcut_test");
 }
 
+/// Copy of alias_path() in src/paths.rs
+pub fn alias_path() -> PathBuf {
+    return home_path().join(".jumpcut");
+}
+
+/// Copy of stats_path() in src/paths.rs
+fn stats_path() -> PathBuf {
+    return home_path().join(".jumpcut_stats");
+}
+
+/// Shared alias folder used by tests (pass it via JUMPCUT_SHARED_PATH)
+pub fn shared_path() -> PathBuf {
+    let pwd = env::current_dir().unwrap_or_default();
+    return pwd.join(".jumpcut_test_shared");
+}
+
+/// Copy of trust_path() in src/paths.rs
+fn trust_path() -> PathBuf {
+    return home_path().join(".jumpcut_trust");
+}
+
+/// Copy of config_path() in src/paths.rs
+pub fn config_path() -> PathBuf {
+    return home_path().join(".jumpcut_config.toml");
+}
+
+/// Empty folder for temporary files created by a test (e.g. pass it via TMPDIR)
+pub fn scratch_path() -> PathBuf {
+    let pwd = env::current_dir().unwrap_or_default();
+    return pwd.join(".jumpcut_test_scratch");
+}
+
 /// Does a given alias exist?
 pub fn alias_exists(al: &str) -> bool {
     let path = alias_path().join(al);
     return path.exists();
 }
 
+/// Writes the raw contents of an alias file
+pub fn write_alias_file(al: &str, contents: &str) {
+    fs::write(alias_path().join(al), contents).unwrap();
+}
+
+/// Reads the raw contents of an alias file
+pub fn read_alias_file(al: &str) -> String {
+    return fs::read_to_string(alias_path().join(al)).unwrap();
+}
+
 /// Removes everything from the test alias directory
 fn remove_all_aliases() -> io::Result<()> {
     for entry in fs::read_dir(alias_path())? {